@@ -1,14 +1,22 @@
 mod options;
+mod output;
 
-use ai::Message;
+use ai::{Message, Role};
+use ai_macros::Tool;
 use anyhow::Result;
 use clap::Parser as _;
 use dotenv::dotenv;
-use log::{LevelFilter, error, info};
-use options::{Commands, Options};
+use log::{LevelFilter, debug, error, info, warn};
+use options::{Commands, ModelSort, Options};
+use output::ResponseOutput;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader};
+use tokio_util::sync::CancellationToken;
 
 /// Parses the program arguments and returns None, if no arguments were provided and Some otherwise.
 fn parse_args() -> Result<Options> {
@@ -34,6 +42,8 @@ fn initialize_logging(filter: LevelFilter) {
             )
         })
         .filter_level(filter)
+        // never mix log messages into the responses written to stdout or an output file
+        .target(env_logger::Target::Stderr)
         .init();
 }
 
@@ -64,94 +74,691 @@ async fn run_program() -> Result<()> {
 
     info!("Create client...");
     let mut client = ai::Client::new(api_key, options.api_endpoint.parse()?)?;
+
+    let proxy = options
+        .proxy
+        .clone()
+        .or_else(|| std::env::var("HTTPS_PROXY").ok())
+        .or_else(|| std::env::var("HTTP_PROXY").ok());
+    if let Some(proxy) = proxy {
+        client = client.with_proxy(proxy.parse()?)?;
+    }
+
+    if let Some(budget) = options.budget {
+        client = client.with_budget(Arc::new(ai::Budget::new(budget)));
+
+        // the budget can only be enforced if the pricing of the models is known
+        client.get_models().await?;
+    }
     info!("Create client...Ok");
 
     match options.command {
         Commands::Models(models_options) => {
-            command_list_models(&mut client, &models_options).await?;
+            command_list_models(&client, &models_options).await?;
         }
         Commands::Prompt(prompt_options) => {
-            command_prompt(&mut client, &prompt_options).await?;
+            command_prompt(&client, &prompt_options).await?;
+        }
+        Commands::Chat(chat_options) => {
+            command_chat(&client, &chat_options).await?;
+        }
+        Commands::Batch(batch_options) => {
+            command_batch(&client, &batch_options).await?;
         }
         Commands::Weather(weather_options) => {
-            command_weather(&mut client, &weather_options).await?;
+            command_weather(&client, &weather_options).await?;
         }
     }
 
     Ok(())
 }
 
+/// Returns true if the given model matches the filters of the models command.
+///
+/// # Arguments
+/// * `model` - The model to check.
+/// * `models_options` - The options for the command.
+fn model_matches_filters(
+    model: &ai::LLMModel,
+    models_options: &options::QueryModelsArguments,
+) -> bool {
+    if let Some(search_string) = &models_options.search_string
+        && !model.name.to_lowercase().contains(search_string)
+    {
+        return false;
+    }
+
+    if models_options.structured_output
+        && !model.supported_parameters.contains("structured_outputs")
+    {
+        return false;
+    }
+
+    if models_options.tool_choice && !model.supported_parameters.contains("tool_choice") {
+        return false;
+    }
+
+    if models_options.function_calling && !model.supported_parameters.contains("tools") {
+        return false;
+    }
+
+    if let Some(modality) = &models_options.modality
+        && !model.supports_input_modality(modality)
+    {
+        return false;
+    }
+
+    if let Some(max_price) = models_options.max_prompt_price
+        && !price_at_most(model, model.pricing.prompt_per_token(), max_price)
+    {
+        return false;
+    }
+
+    if let Some(max_price) = models_options.max_completion_price
+        && !price_at_most(model, model.pricing.completion_per_token(), max_price)
+    {
+        return false;
+    }
+
+    true
+}
+
+/// Returns true if the given price of the model is at or below the maximum price.
+/// Models with an unknown price are skipped with a warning.
+///
+/// # Arguments
+/// * `model` - The model the price belongs to.
+/// * `price` - The price in USD per token.
+/// * `max_price` - The maximum price in USD per token.
+fn price_at_most(model: &ai::LLMModel, price: ai::Result<f64>, max_price: f64) -> bool {
+    match price {
+        Ok(price) => price <= max_price,
+        Err(err) => {
+            warn!("Skipping model {}: {}", model.id, err);
+            false
+        }
+    }
+}
+
+/// The number of days before the deprecation of a model from which on a warning is shown.
+const DEPRECATION_WARNING_DAYS: i64 = 30;
+
 /// The command to list the models available in the API
 ///
 /// # Arguments
 /// * `client` - The client to use for the API requests.
 /// * `models_options` - The options for the command.
 async fn command_list_models(
-    client: &mut ai::Client,
+    client: &ai::Client,
     models_options: &options::QueryModelsArguments,
 ) -> Result<()> {
     let models = client.get_models().await?;
 
-    for model in models.get_models() {
-        if let Some(search_string) = &models_options.search_string {
-            if !model.name.to_lowercase().contains(search_string) {
-                continue;
-            }
-        }
+    let mut filtered_models: Vec<&ai::LLMModel> = models
+        .with_min_context(models_options.min_context.unwrap_or(0))
+        .filter(|model| model_matches_filters(model, models_options))
+        .collect();
+
+    if let Some(sort_by) = models_options.sort_by {
+        let key = match sort_by {
+            ModelSort::Price => ai::ModelSortKey::PromptPrice,
+            ModelSort::Context => ai::ModelSortKey::ContextLength,
+            ModelSort::Name => ai::ModelSortKey::Name,
+            ModelSort::Created => ai::ModelSortKey::Created,
+        };
+
+        let order = if models_options.reverse {
+            ai::SortOrder::Descending
+        } else {
+            ai::SortOrder::Ascending
+        };
+
+        ai::sort_models(&mut filtered_models, key, order);
+    }
 
-        if models_options.structured_output
-            && !model.supported_parameters.contains("structured_outputs")
-        {
-            continue;
+    if models_options.markdown {
+        let mut columns = vec!["id", "name", "context"];
+        if models_options.show_pricing {
+            columns.extend(["prompt_price", "completion_price"]);
         }
 
-        if models_options.tool_choice && !model.supported_parameters.contains("tool_choice") {
-            continue;
-        }
+        print!("{}", ai::format_markdown_table(filtered_models, &columns));
 
-        if models_options.function_calling && !model.supported_parameters.contains("tools") {
-            continue;
-        }
+        return Ok(());
+    }
 
+    for model in filtered_models {
         println!("Model: {}", model.name);
         println!("  ID: {}", model.id);
         println!("  Context length: {}", model.context_length);
 
+        if model.is_deprecated() {
+            println!("  ⚠ DEPRECATED");
+        } else if let Some(days) = model.days_until_deprecation()
+            && days <= DEPRECATION_WARNING_DAYS
+        {
+            println!("  ⚠ Deprecating in {} days", days);
+        }
+
         if models_options.show_pricing {
             println!("  Pricing: {}", model.pricing);
+
+            let example_cost = model.pricing.estimated_cost(10_000, 2_000);
+            if !example_cost.is_nan() {
+                println!(
+                    "  Cost for 10K prompt + 2K completion tokens: ${:.4}",
+                    example_cost
+                );
+            }
         }
     }
 
     Ok(())
 }
 
+/// Parses the model requirements of the `--auto-model` flag, i.e., a comma-separated list
+/// of supported parameters, `context=<tokens>` and `max-price=<USD per prompt token>`.
+///
+/// # Arguments
+/// * `spec` - The requirements given to the flag.
+fn parse_model_requirements(spec: &str) -> Result<ai::ModelRequirements> {
+    let mut requirements = ai::ModelRequirements::default();
+
+    for requirement in spec.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+        match requirement.split_once('=') {
+            Some(("context", value)) => requirements.min_context = value.trim().parse()?,
+            Some(("max-price", value)) => {
+                requirements.max_prompt_price = Some(value.trim().parse()?)
+            }
+            Some((key, _)) => anyhow::bail!("Unknown model requirement: {}", key),
+            None => requirements
+                .supported_parameters
+                .push(requirement.to_string()),
+        }
+    }
+
+    Ok(requirements)
+}
+
+/// Parses the variables of the `--template-var` flags given as `key=value`.
+///
+/// # Arguments
+/// * `template_vars` - The values given to the flags.
+fn parse_template_vars(template_vars: &[String]) -> Result<HashMap<&str, &str>> {
+    template_vars
+        .iter()
+        .map(|var| match var.split_once('=') {
+            Some((key, value)) => Ok((key.trim(), value)),
+            None => anyhow::bail!("Invalid template variable, expected key=value: {}", var),
+        })
+        .collect()
+}
+
+/// Selects the model for the given `--auto-model` flag and returns its id.
+///
+/// # Arguments
+/// * `client` - The client to use for the API requests.
+/// * `auto_model` - The value of the flag, either "best" or the model requirements.
+async fn select_model(client: &ai::Client, auto_model: &str) -> Result<String> {
+    let models = client.get_models().await?;
+
+    let model = if auto_model == "best" {
+        models.sorted_by_context_length_desc().into_iter().next()
+    } else {
+        models.cheapest_with_requirements(&parse_model_requirements(auto_model)?)
+    };
+
+    match model {
+        Some(model) => Ok(model.id.clone()),
+        None => anyhow::bail!("No model meets the requirements: {}", auto_model),
+    }
+}
+
+/// The size in bytes from which on a prompt file is considered large.
+const LARGE_PROMPT_FILE_SIZE: usize = 1024 * 1024;
+
+/// Reads the whole content of the given file as UTF-8 or of stdin if the path is "-".
+///
+/// # Arguments
+/// * `path` - The path of the file to read.
+async fn read_file_or_stdin(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        let mut content = String::new();
+        tokio::io::stdin().read_to_string(&mut content).await?;
+        return Ok(content);
+    }
+
+    tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))
+}
+
 async fn command_prompt(
-    client: &mut ai::Client,
+    client: &ai::Client,
     prompt_options: &options::PromptArguments,
 ) -> Result<()> {
+    // stdin can only be read once
+    let stdin = Some(Path::new("-"));
+    if prompt_options.prompt_file.as_deref() == stdin
+        && prompt_options.system_prompt_file.as_deref() == stdin
+    {
+        anyhow::bail!("Only one of --file and --system-file can read from stdin");
+    }
+
+    let prompt = match (&prompt_options.prompt, &prompt_options.prompt_file) {
+        (Some(_), Some(_)) => anyhow::bail!("Only one of --prompt and --file can be provided"),
+        (Some(prompt), None) => prompt.clone(),
+        (None, Some(path)) => {
+            let prompt = read_file_or_stdin(path).await?;
+            if prompt.len() > LARGE_PROMPT_FILE_SIZE {
+                warn!("Large prompt file; ensure it fits in the model's context window");
+            }
+
+            prompt
+        }
+        (None, None) => anyhow::bail!("Either --prompt or --file must be provided"),
+    };
+
     let prompt = Message {
-        role: "user".to_string(),
+        role: Role::User,
         tool_call_id: String::new(),
-        content: prompt_options.prompt.clone(),
+        content: prompt.into(),
         tool_calls: vec![],
+        refusal: None,
+        reasoning: None,
+    };
+
+    let model = match (&prompt_options.model, &prompt_options.auto_model) {
+        (Some(model), _) => model.clone(),
+        (None, Some(auto_model)) => {
+            let model = select_model(client, auto_model).await?;
+            info!("Selected model {}", model);
+            model
+        }
+        (None, None) => anyhow::bail!("Either --model or --auto-model must be provided"),
     };
 
-    let prompt_parameters =
-        ai::ChatCompletionParameter::new(prompt_options.model.clone(), vec![prompt]);
+    let system = match (&prompt_options.system, &prompt_options.system_prompt_file) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("Only one of --system and --system-file can be provided");
+        }
+        (Some(system), None) => Some(system.clone()),
+        (None, Some(path)) => Some(read_file_or_stdin(path).await?),
+        (None, None) => None,
+    };
 
-    let response = client.chat_completion(&prompt_parameters).await?;
+    let mut messages = Vec::new();
+    if let Some(system) = system {
+        if prompt_options.template_vars.is_empty() {
+            messages.push(Message::system(system));
+        } else {
+            let vars = parse_template_vars(&prompt_options.template_vars)?;
+            let template = ai::PromptTemplate::new(system);
+            messages.push(Message::system_from_template(&template, &vars)?);
+        }
+    }
+    messages.push(prompt);
+
+    let mut prompt_parameters = ai::ChatCompletionParameter::new(model.clone(), messages);
+
+    if let Some(max_tokens) = prompt_options.max_tokens {
+        prompt_parameters.set_max_tokens(max_tokens);
+    }
+
+    // setting both logs a warning, as most providers recommend only one of them
+    if let Some(temperature) = prompt_options.temperature {
+        prompt_parameters.set_temperature(temperature)?;
+    }
 
-    for choice in response {
-        println!("Response: {}", choice.message.content);
+    if let Some(top_p) = prompt_options.top_p {
+        prompt_parameters.set_top_p(top_p)?;
+    }
+
+    if let Some(seed) = prompt_options.seed {
+        prompt_parameters.set_seed(seed);
+    }
+
+    let mut output =
+        ResponseOutput::new(prompt_options.output_file.as_deref(), prompt_options.tee).await?;
+
+    // abort the request on Ctrl-C instead of waiting for the response
+    let token = CancellationToken::new();
+    let ctrl_c_token = token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            ctrl_c_token.cancel();
+        }
+    });
+
+    let response = client
+        .chat_completion_full_with_cancel(&prompt_parameters, token)
+        .await?;
+
+    debug!("Completion tokens: {}", response.usage.completion_tokens);
+
+    if prompt_options.seed.is_some() {
+        info!("System fingerprint: {:?}", response.system_fingerprint);
+    }
+
+    for choice in response.choices.iter() {
+        let content = choice.message.content.to_text();
+        if prompt_options.json_output {
+            match serde_json::from_str::<serde_json::Value>(&ai::json_types::extract_json(&content))
+            {
+                Ok(json) => {
+                    output
+                        .write_response("", &serde_json::to_string_pretty(&json)?)
+                        .await?
+                }
+                Err(_) => {
+                    warn!("Response is not valid JSON");
+                    output.write_response("", &content).await?;
+                }
+            }
+        } else {
+            output.write_response("Response: ", &content).await?;
+        }
+    }
+
+    if prompt_options.show_cost {
+        let models = client.get_models().await?;
+        match models.get_models().iter().find(|m| m.id == model) {
+            Some(model) => {
+                let cost = response.usage.total_cost(&model.pricing)?;
+                println!("Estimated cost: ${:.6}", cost);
+            }
+            None => warn!("No pricing found for model {}", model),
+        }
+    }
+
+    Ok(())
+}
+
+/// The REPL command to end the chat.
+const EXIT_COMMAND: &str = "!exit";
+
+/// The REPL command to save the conversation to the history file.
+const SAVE_COMMAND: &str = "!save";
+
+/// Loads the conversation from the given history file without blocking the runtime.
+///
+/// # Arguments
+/// * `path` - The path of the history file.
+async fn load_conversation(path: &Path) -> Result<ai::Conversation<'static>> {
+    let path = path.to_path_buf();
+    let conversation = tokio::task::spawn_blocking(move || ai::Conversation::load(&path)).await??;
+
+    Ok(conversation)
+}
+
+/// Saves the conversation to the given history file without blocking the runtime.
+///
+/// # Arguments
+/// * `path` - The path of the history file.
+/// * `conversation` - The conversation to save.
+async fn save_conversation(path: &Path, conversation: &ai::Conversation<'static>) -> Result<()> {
+    let path = path.to_path_buf();
+    let conversation = conversation.clone();
+    tokio::task::spawn_blocking(move || conversation.save(&path)).await??;
+
+    Ok(())
+}
+
+/// The command to chat interactively with the LLM.
+/// The chat ends on EOF, i.e., Ctrl-D, or with the `!exit` command.
+/// If a history file is given, the conversation is loaded from it and saved to it
+/// after each turn, on exit and with the `!save` command.
+///
+/// # Arguments
+/// * `client` - The client to use for the API requests.
+/// * `chat_options` - The options for the command.
+async fn command_chat(client: &ai::Client, chat_options: &options::ChatArguments) -> Result<()> {
+    let mut conversation = match &chat_options.history_file {
+        Some(history_file) if tokio::fs::try_exists(history_file).await? => {
+            let mut conversation = load_conversation(history_file).await?;
+            info!(
+                "Loaded {} messages from {}",
+                conversation.history().len(),
+                history_file.display()
+            );
+
+            // legacy history files do not contain the model
+            if conversation.parameter().model().is_empty() {
+                conversation
+                    .parameter_mut()
+                    .set_model(chat_options.model.clone());
+            } else if conversation.parameter().model() != chat_options.model {
+                info!(
+                    "Continuing the conversation with {} instead of {}",
+                    chat_options.model,
+                    conversation.parameter().model()
+                );
+                conversation
+                    .parameter_mut()
+                    .set_model(chat_options.model.clone());
+            }
+
+            conversation
+        }
+        _ => ai::Conversation::new(ai::ChatCompletionParameter::new(
+            chat_options.model.clone(),
+            vec![],
+        )),
+    };
+
+    if let Some(temperature) = chat_options.temperature {
+        conversation.parameter_mut().set_temperature(temperature)?;
+    }
+
+    if let Some(system) = &chat_options.system {
+        if conversation.history().is_empty() {
+            conversation
+                .parameter_mut()
+                .add_message(Message::system(system.clone()));
+        } else {
+            warn!("Ignoring the system prompt, as the conversation has been loaded from history");
+        }
+    }
+
+    let mut output =
+        ResponseOutput::new(chat_options.output_file.as_deref(), chat_options.tee).await?;
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+
+        let Some(line) = lines.next_line().await? else {
+            println!();
+            break;
+        };
+
+        let line = line.trim();
+        if line == EXIT_COMMAND {
+            break;
+        }
+
+        if line == SAVE_COMMAND {
+            match &chat_options.history_file {
+                Some(history_file) => {
+                    save_conversation(history_file, &conversation).await?;
+                    println!("Saved conversation to {}", history_file.display());
+                }
+                None => println!("No history file given, use --history-file"),
+            }
+            continue;
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let turn = match conversation.send_user(client, line).await {
+            Ok(turn) => turn,
+            Err(err) => {
+                error!("Chat completion failed: {}", err);
+                continue;
+            }
+        };
+
+        if let Some(refusal) = turn.message.refusal.as_deref() {
+            output.write_response("Refused: ", refusal).await?;
+        } else {
+            output.write_response("", &turn.content()).await?;
+        }
+
+        // no tools are registered, so tool calls are only reported and answered with an error
+        for tool_call in turn.tool_calls() {
+            let call = format!(
+                "{}({})",
+                tool_call.function_call.name, tool_call.function_call.arguments
+            );
+            output
+                .write_response("Tool call requested: ", &call)
+                .await?;
+
+            conversation.resolve_tool_call(&tool_call.id, "Error: No tools are available")?;
+        }
+
+        if let Some(history_file) = &chat_options.history_file
+            && !chat_options.no_autosave
+        {
+            save_conversation(history_file, &conversation).await?;
+        }
+    }
+
+    if let Some(history_file) = &chat_options.history_file {
+        save_conversation(history_file, &conversation).await?;
+        info!("Saved conversation to {}", history_file.display());
     }
 
     Ok(())
 }
 
-/// The parameter for the weather tool.
-#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+/// A single request of the input file of the batch command.
+#[derive(Deserialize, Debug)]
+struct BatchRequest {
+    pub id: String,
+    pub prompt: String,
+}
+
+/// A single response of the output file of the batch command.
+#[derive(Serialize, Debug)]
+struct BatchResponse<'a> {
+    pub id: &'a str,
+    pub response: String,
+}
+
+/// A single failed request of the error output file of the batch command.
+#[derive(Serialize, Debug)]
+struct BatchFailure<'a> {
+    pub id: &'a str,
+    pub error: String,
+}
+
+/// Appends the given value as a single JSON line to the file.
+///
+/// # Arguments
+/// * `file` - The file to write to.
+/// * `value` - The value to write.
+async fn write_json_line<T: Serialize>(file: &mut tokio::fs::File, value: &T) -> Result<()> {
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    file.write_all(line.as_bytes()).await?;
+
+    Ok(())
+}
+
+/// The command to send all prompts of a JSONL file concurrently to the LLM.
+/// Failed requests are reported, but do not abort the batch.
+///
+/// # Arguments
+/// * `client` - The client to use for the API requests.
+/// * `batch_options` - The options for the command.
+async fn command_batch(client: &ai::Client, batch_options: &options::BatchArguments) -> Result<()> {
+    if batch_options.concurrency == 0 {
+        anyhow::bail!("The concurrency must be at least 1");
+    }
+
+    let input = read_file_or_stdin(&batch_options.input).await?;
+    let mut requests = Vec::new();
+    for (line_number, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let request: BatchRequest = serde_json::from_str(line)
+            .map_err(|e| anyhow::anyhow!("Invalid request in line {}: {}", line_number + 1, e))?;
+        requests.push(request);
+    }
+
+    let mut output = tokio::fs::File::create(&batch_options.output).await?;
+    let mut error_output = match &batch_options.error_output {
+        Some(path) => Some(tokio::fs::File::create(path).await?),
+        None => None,
+    };
+
+    let params = requests
+        .iter()
+        .map(|request| {
+            ai::ChatCompletionParameter::new(
+                batch_options.model.clone(),
+                vec![Message::user(request.prompt.clone())],
+            )
+        })
+        .collect();
+    let results = client
+        .chat_completion_batch(params, batch_options.concurrency)
+        .await;
+
+    let mut num_succeeded = 0;
+    for (request, result) in requests.iter().zip(results) {
+        let result = result.and_then(|choices| {
+            choices.into_iter().next().ok_or_else(|| {
+                ai::Error::InternalError("The response contains no choices".to_string())
+            })
+        });
+
+        match result {
+            Ok(choice) => {
+                let response = BatchResponse {
+                    id: &request.id,
+                    response: choice.message.content.to_text(),
+                };
+                write_json_line(&mut output, &response).await?;
+                num_succeeded += 1;
+            }
+            Err(err) => {
+                error!("Request {} failed: {}", request.id, err);
+
+                if let Some(error_output) = error_output.as_mut() {
+                    let failure = BatchFailure {
+                        id: &request.id,
+                        error: err.to_string(),
+                    };
+                    write_json_line(error_output, &failure).await?;
+                }
+            }
+        }
+    }
+
+    eprintln!("{}/{} requests succeeded", num_succeeded, requests.len());
+
+    output.flush().await?;
+    if let Some(error_output) = error_output.as_mut() {
+        error_output.flush().await?;
+    }
+
+    Ok(())
+}
+
+/// The maximum number of chat completion requests in a tool loop.
+const MAX_TOOL_ITERATIONS: usize = 5;
+
+/// Get current temperature for a given location.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Tool)]
 #[schemars(deny_unknown_fields)]
-struct WeatherParameter {
+struct GetWeather {
     /// The latitude of the location.
     pub latitude: f64,
 
@@ -174,7 +781,7 @@ struct WeatherData {
 ///
 /// # Arguments
 /// * `client` - The client to use for the API requests.
-async fn get_weather(parameter: &WeatherParameter) -> Result<f64> {
+async fn get_weather(parameter: &GetWeather) -> Result<f64> {
     let url_str = format!(
         "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=temperature_2m,wind_speed_10m&hourly=temperature_2m,relative_humidity_2m,wind_speed_10m",
         parameter.latitude, parameter.longitude
@@ -193,51 +800,67 @@ async fn get_weather(parameter: &WeatherParameter) -> Result<f64> {
 }
 
 async fn command_weather(
-    client: &mut ai::Client,
+    client: &ai::Client,
     prompt_options: &options::WeatherArguments,
 ) -> Result<()> {
-    let prompt = Message {
-        role: "user".to_string(),
-        tool_call_id: String::new(),
-        content: "What is the weather like in Paris today?".to_string(),
-        tool_calls: vec![],
-    };
-
     let mut prompt_parameters =
-        ai::ChatCompletionParameter::new(prompt_options.model.clone(), vec![prompt]);
+        ai::ChatCompletionParameter::new(prompt_options.model.clone(), Vec::new());
 
-    prompt_parameters.set_tool_choice(ai::ToolChoice::Required)?;
+    if let Some(temperature) = prompt_options.temperature {
+        prompt_parameters.set_temperature(temperature)?;
+    }
 
-    prompt_parameters.add_tool(ai::Tool::<WeatherParameter>::new(
-        "get_weather".to_string(),
-        "Get current temperature for a given location.".to_string(),
-    ));
+    let mut registry = ai::ToolRegistry::new();
+    registry.register(GetWeather::into_tool(), |weather_func_call| {
+        Box::pin(async move {
+            info!("Tool call: {:?}", weather_func_call);
 
-    let response = client.chat_completion(&prompt_parameters).await?;
+            let result = get_weather(&weather_func_call)
+                .await
+                .map_err(|e| ai::Error::InternalError(e.to_string()))?;
+            info!("Weather result: {:?}", result);
 
-    prompt_parameters.add_message(response[0].message.clone());
+            Ok(format!("The current temperature is {}°C", result))
+        })
+    });
+    registry.apply_to(&mut prompt_parameters)?;
+    prompt_parameters.set_tool_choice(ai::ToolChoice::Auto)?;
 
-    let tool_call = &response[0].message.tool_calls[0];
-    let weather_func_call: WeatherParameter =
-        serde_json::from_str(&tool_call.function_call.arguments)?;
-    info!("Tool call: {:?}", tool_call);
-    let result = get_weather(&weather_func_call).await?;
-    info!("Weather result: {:?}", result);
+    let mut output =
+        ResponseOutput::new(prompt_options.output_file.as_deref(), prompt_options.tee).await?;
 
-    prompt_parameters.add_message(Message {
-        role: "tool".to_string(),
-        tool_call_id: tool_call.id.clone(),
-        content: format!("The current temperature is {}°C", result),
-        tool_calls: vec![],
-    });
+    let mut conversation = ai::Conversation::new(prompt_parameters);
+    let mut turn = conversation
+        .send_user(client, "What is the weather like in Paris today?")
+        .await?;
 
-    prompt_parameters.set_tool_choice(ai::ToolChoice::Auto)?;
-    let response = client.chat_completion(&prompt_parameters).await?;
+    for _ in 1..MAX_TOOL_ITERATIONS {
+        if !conversation.is_waiting_for_tools() {
+            break;
+        }
+
+        let tool_calls = conversation.pending_tool_calls().to_vec();
+        for (tool_call_id, result) in ai::dispatch_all_tool_calls(&tool_calls, &registry).await {
+            let content = result.unwrap_or_else(|err| {
+                warn!("Tool call {} failed: {}", tool_call_id, err);
+                format!("Error: {}", err)
+            });
+
+            conversation.resolve_tool_call(&tool_call_id, &content)?;
+        }
 
-    for choice in response {
-        println!("Response: {}", choice.message.content);
+        turn = conversation.send(client).await?;
     }
 
+    if conversation.is_waiting_for_tools() {
+        anyhow::bail!(
+            "The model still requests tool calls after {} requests",
+            MAX_TOOL_ITERATIONS
+        );
+    }
+
+    output.write_response("Response: ", &turn.content()).await?;
+
     Ok(())
 }
 
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use std::io::Write as _;
+use std::path::Path;
+use tokio::io::AsyncWriteExt as _;
+
+/// The destination of the responses of a command, i.e., stdout, a file or both.
+pub struct ResponseOutput {
+    file: Option<tokio::fs::File>,
+    tee: bool,
+}
+
+impl ResponseOutput {
+    /// Creates the output for the given options.
+    /// The output file is created or truncated immediately.
+    ///
+    /// # Arguments
+    /// * `output_file` - The optional file to write the responses to instead of stdout.
+    /// * `tee` - Whether to write the responses to stdout in addition to the file.
+    pub async fn new(output_file: Option<&Path>, tee: bool) -> Result<Self> {
+        let file = match output_file {
+            Some(path) => Some(tokio::fs::File::create(path).await.map_err(|e| {
+                anyhow::anyhow!("Failed to create output file {}: {}", path.display(), e)
+            })?),
+            None => None,
+        };
+
+        Ok(Self { file, tee })
+    }
+
+    /// Writes a response followed by a newline.
+    ///
+    /// # Arguments
+    /// * `prefix` - The prefix of the response, only printed to stdout, e.g., "Response: ".
+    /// * `content` - The content of the response.
+    pub async fn write_response(&mut self, prefix: &str, content: &str) -> Result<()> {
+        match self.file.as_mut() {
+            Some(file) => {
+                file.write_all(content.as_bytes()).await?;
+                file.write_all(b"\n").await?;
+                file.flush().await?;
+
+                if self.tee {
+                    println!("{}{}", prefix, content);
+                }
+            }
+            None => {
+                println!("{}{}", prefix, content);
+                std::io::stdout().flush()?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use convert_case::{Case, Casing};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Attribute, DeriveInput, Expr, Lit, Meta, parse_macro_input};
+
+/// Derives an associated function `into_tool() -> ai::Tool<Self>` for a tool parameter struct.
+/// The name of the tool is the name of the struct in snake case, e.g., `GetWeather` becomes
+/// `get_weather`, and the description of the tool is the doc comment of the struct.
+#[proc_macro_derive(Tool)]
+pub fn derive_tool(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let ident = &input.ident;
+    let name = ident.to_string().to_case(Case::Snake);
+    let description = doc_comment(&input.attrs);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Creates the tool with this struct as parameters.
+            pub fn into_tool() -> ::ai::Tool<Self> {
+                ::ai::Tool::new(#name.to_string(), #description.to_string())
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+/// Returns the doc comment given by the attributes, with the lines trimmed and joined.
+///
+/// # Arguments
+/// * `attrs` - The attributes of the item.
+fn doc_comment(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(s) => Some(s.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    lines.join("\n").trim().to_string()
+}
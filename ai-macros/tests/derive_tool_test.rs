@@ -0,0 +1,42 @@
+use ai_macros::Tool;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Get current temperature for a given location.
+/// The location is given by its name.
+#[derive(Deserialize, JsonSchema, Tool)]
+#[allow(dead_code)]
+struct GetWeather {
+    /// City and country e.g. Bogotá, Colombia
+    location: String,
+}
+
+#[derive(Deserialize, JsonSchema, Tool)]
+#[allow(dead_code)]
+struct HTTPRequest {
+    url: String,
+}
+
+#[test]
+fn test_derive_tool() {
+    let tool = GetWeather::into_tool();
+    assert_eq!(tool.name(), "get_weather");
+    assert_eq!(
+        tool.description(),
+        "Get current temperature for a given location.\nThe location is given by its name."
+    );
+
+    let tool = tool.into_json();
+    let tool_json = serde_json::to_value(&tool).unwrap();
+    assert_eq!(
+        tool_json["function"]["parameters"]["properties"]["location"]["type"],
+        "string"
+    );
+}
+
+#[test]
+fn test_derive_tool_without_doc_comment() {
+    let tool = HTTPRequest::into_tool();
+    assert_eq!(tool.name(), "http_request");
+    assert_eq!(tool.description(), "");
+}
@@ -0,0 +1,124 @@
+use log::{debug, warn};
+
+use crate::tools::{execute_parallel, execute_sequential};
+use crate::{
+    ChatCompletionParameter, Client, Error, JsonToolCall, Message, Result, ToolRegistry,
+    UsageTracker,
+};
+
+/// The result of a successful agent run.
+#[derive(Debug, Clone)]
+pub struct AgentResult {
+    /// The content of the final answer of the model.
+    pub final_content: String,
+
+    /// The accumulated token usage of all requests of the run.
+    pub usage: UsageTracker,
+
+    /// The number of chat completion requests of the run.
+    pub iterations: usize,
+
+    /// All tool calls requested by the model during the run.
+    pub tool_calls: Vec<JsonToolCall>,
+}
+
+/// An agent following the Reason+Act pattern, i.e., the model alternates between
+/// requesting tool calls and reasoning about their results until it gives a final answer.
+pub struct ReactAgent {
+    client: Client,
+    registry: ToolRegistry,
+    max_iterations: usize,
+    sequential_tool_calls: bool,
+}
+
+impl ReactAgent {
+    /// Creates a new agent dispatching the tool calls of a response in parallel.
+    ///
+    /// # Arguments
+    /// * `client` - The client used for the chat completion requests.
+    /// * `registry` - The registry with the tools of the agent.
+    /// * `max_iterations` - The maximum number of chat completion requests per run.
+    pub fn new(client: Client, registry: ToolRegistry, max_iterations: usize) -> Self {
+        Self {
+            client,
+            registry,
+            max_iterations,
+            sequential_tool_calls: false,
+        }
+    }
+
+    /// Dispatches the tool calls of a response one after another instead of in parallel,
+    /// e.g., if the tools depend on the side effects of each other.
+    pub fn with_sequential_tool_calls(mut self) -> Self {
+        self.sequential_tool_calls = true;
+        self
+    }
+
+    /// Runs the agent loop, i.e., sends the chat completion request, dispatches the requested
+    /// tool calls, adds their results to the conversation and repeats until the model stops
+    /// requesting tool calls.
+    /// Failed tool calls are reported to the model as error messages.
+    /// The tools of the registry are added to the parameter, so it must not contain them already.
+    ///
+    /// # Arguments
+    /// * `params` - The parameter for the chat completion requests.
+    pub async fn run(&self, mut params: ChatCompletionParameter<'_>) -> Result<AgentResult> {
+        self.registry.apply_to(&mut params)?;
+
+        let mut usage = UsageTracker::new();
+        let mut tool_calls = Vec::new();
+
+        for iteration in 1..=self.max_iterations {
+            let response = self.client.chat_completion_full(&params).await?;
+            usage.add(&response.usage);
+
+            let Some(choice) = response.choices.into_iter().next() else {
+                return Err(Error::InternalError(
+                    "The response did not contain any choices".to_string(),
+                ));
+            };
+
+            if choice.message.tool_calls.is_empty() {
+                return Ok(AgentResult {
+                    final_content: choice.message.content.to_text(),
+                    usage,
+                    iterations: iteration,
+                    tool_calls,
+                });
+            }
+
+            debug!(
+                "Iteration {}: dispatching {} tool calls",
+                iteration,
+                choice.message.tool_calls.len()
+            );
+
+            let calls = choice.message.tool_calls.clone();
+            params.add_message(choice.message);
+
+            let results = if self.sequential_tool_calls {
+                execute_sequential(&calls, &self.registry).await
+            } else {
+                execute_parallel(&calls, &self.registry).await
+            };
+
+            for (tool_call_id, result) in results {
+                let content = match result {
+                    Ok(content) => content,
+                    Err(err) => {
+                        warn!("Tool call {} failed: {}", tool_call_id, err);
+                        format!("Error: {}", err)
+                    }
+                };
+
+                params.add_message(Message::tool_result(tool_call_id, content));
+            }
+
+            tool_calls.extend(calls);
+        }
+
+        Err(Error::MaxIterationsReached {
+            history: params.messages().to_vec(),
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{Error, Message, Result};
+
+/// A spending limit in USD shared by all requests of one or more clients.
+/// The spent amount is stored as bit-cast `f64` for lock-free updates.
+#[derive(Debug)]
+pub struct Budget {
+    max_spend_usd: f64,
+    spent_usd: AtomicU64,
+}
+
+impl Budget {
+    /// Creates a new budget with nothing spent yet.
+    ///
+    /// # Arguments
+    /// * `max_spend_usd` - The maximum amount to spend in USD.
+    pub fn new(max_spend_usd: f64) -> Self {
+        Self {
+            max_spend_usd,
+            spent_usd: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    /// Returns the maximum amount to spend in USD.
+    pub fn max_spend_usd(&self) -> f64 {
+        self.max_spend_usd
+    }
+
+    /// Returns the amount spent so far in USD.
+    pub fn spent_usd(&self) -> f64 {
+        f64::from_bits(self.spent_usd.load(Ordering::Acquire))
+    }
+
+    /// Returns the amount that can still be spent in USD.
+    pub fn remaining_usd(&self) -> f64 {
+        (self.max_spend_usd - self.spent_usd()).max(0.0)
+    }
+
+    /// Checks that spending the given additional cost stays within the budget.
+    ///
+    /// # Arguments
+    /// * `cost` - The additional cost in USD.
+    pub fn check(&self, cost: f64) -> Result<()> {
+        let current = self.spent_usd() + cost;
+        if current > self.max_spend_usd {
+            return Err(Error::BudgetExceeded {
+                limit: self.max_spend_usd,
+                current,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Adds the given cost to the spent amount.
+    ///
+    /// # Arguments
+    /// * `cost` - The cost in USD.
+    pub fn add_spend(&self, cost: f64) {
+        let mut current = self.spent_usd.load(Ordering::Acquire);
+        loop {
+            let spent = f64::from_bits(current) + cost;
+            match self.spent_usd.compare_exchange_weak(
+                current,
+                spent.to_bits(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+/// Roughly estimates the number of tokens of the given messages,
+/// assuming four characters per token.
+///
+/// # Arguments
+/// * `messages` - The messages to estimate the number of tokens for.
+pub(crate) fn estimate_tokens(messages: &[Message]) -> u64 {
+    let chars: usize = messages
+        .iter()
+        .map(|message| message.content.to_text().chars().count())
+        .sum();
+
+    chars.div_ceil(4) as u64
+}
@@ -0,0 +1,73 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lru::LruCache;
+
+use crate::platform::Instant;
+use crate::{ChatCompletionParameter, Choice};
+
+/// A least recently used cache for the choices of chat completion requests.
+pub(crate) struct ResponseCache {
+    entries: Mutex<LruCache<u64, (Instant, Vec<Choice>)>>,
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    /// Creates a new empty cache.
+    ///
+    /// # Arguments
+    /// * `max_entries` - The maximum number of cached responses, at least one.
+    /// * `ttl` - The time after which a cached response expires.
+    pub(crate) fn new(max_entries: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// Returns the cache key for the given parameter, which is a hash of the serialized
+    /// request body, so that requests only share a response if they are sent identically.
+    ///
+    /// # Arguments
+    /// * `parameter` - The parameter for the chat completion request.
+    pub(crate) fn key(parameter: &ChatCompletionParameter<'_>) -> u64 {
+        let request_json = serde_json::to_vec(&parameter.to_request()).unwrap_or_default();
+
+        let mut hasher = DefaultHasher::new();
+        request_json.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns a clone of the cached choices for the given key, if they have not expired.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key of the request.
+    pub(crate) fn get(&self, key: u64) -> Option<Vec<Choice>> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(&key) {
+            Some((inserted, choices)) if inserted.elapsed() < self.ttl => Some(choices.clone()),
+            Some(_) => {
+                entries.pop(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores the choices for the given key, evicting the least recently used entry if full.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key of the request.
+    /// * `choices` - The choices of the response.
+    pub(crate) fn insert(&self, key: u64, choices: Vec<Choice>) {
+        self.entries
+            .lock()
+            .unwrap()
+            .put(key, (Instant::now(), choices));
+    }
+}
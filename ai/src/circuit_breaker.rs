@@ -0,0 +1,219 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::platform::Instant;
+use crate::{Error, Result};
+
+/// The configuration of the circuit breaker, which stops sending requests to an endpoint
+/// after sustained failures, e.g., during an outage of the provider.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive failures after which the circuit opens.
+    pub failure_threshold: u32,
+
+    /// The time window the consecutive failures must occur in.
+    pub window: Duration,
+
+    /// The time the circuit stays open before a probe request is allowed.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The state of the circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+
+    /// Requests fail immediately with `Error::CircuitOpen`.
+    Open { since: Instant },
+
+    /// The cooldown elapsed and a single probe request is allowed.
+    HalfOpen,
+}
+
+/// The mutable state of the circuit breaker.
+struct CircuitBreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    first_failure: Option<Instant>,
+    probe_started: Option<Instant>,
+}
+
+/// Tracks the failures of the requests and opens the circuit on sustained failures.
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<CircuitBreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Creates a new closed circuit breaker.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration of the circuit breaker.
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CircuitBreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                first_failure: None,
+                probe_started: None,
+            }),
+        }
+    }
+
+    /// Returns the current state of the circuit.
+    pub(crate) fn state(&self) -> CircuitState {
+        self.state.lock().unwrap().state
+    }
+
+    /// Checks if a request may be sent and returns `Error::CircuitOpen` otherwise.
+    /// Moves an open circuit to half-open once the cooldown elapsed.
+    pub(crate) fn check(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+
+        match state.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open { since } => {
+                let elapsed = since.elapsed();
+                if elapsed < self.config.cooldown {
+                    return Err(Error::CircuitOpen {
+                        cooldown_remaining: self.config.cooldown - elapsed,
+                    });
+                }
+
+                log::info!("Circuit half-open, sending probe request");
+                state.state = CircuitState::HalfOpen;
+                state.probe_started = Some(Instant::now());
+                Ok(())
+            }
+            CircuitState::HalfOpen => {
+                // allow another probe if the previous one never finished, e.g., was cancelled
+                let probe_started = state.probe_started.unwrap_or_else(Instant::now);
+                let elapsed = probe_started.elapsed();
+                if elapsed < self.config.cooldown {
+                    return Err(Error::CircuitOpen {
+                        cooldown_remaining: self.config.cooldown - elapsed,
+                    });
+                }
+
+                state.probe_started = Some(Instant::now());
+                Ok(())
+            }
+        }
+    }
+
+    /// Records a successful request, which closes the circuit.
+    pub(crate) fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        if state.state != CircuitState::Closed {
+            log::info!("Circuit closed");
+        }
+
+        state.state = CircuitState::Closed;
+        state.consecutive_failures = 0;
+        state.first_failure = None;
+        state.probe_started = None;
+    }
+
+    /// Records a failed request, which opens the circuit if the threshold is reached
+    /// or if the probe request of a half-open circuit failed.
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state.state == CircuitState::HalfOpen {
+            log::warn!("Probe request failed, circuit open again");
+            state.state = CircuitState::Open { since: now };
+            state.probe_started = None;
+            return;
+        }
+
+        // start a new series of failures if the first one is outside of the window
+        match state.first_failure {
+            Some(first_failure) if now.duration_since(first_failure) <= self.config.window => {
+                state.consecutive_failures += 1;
+            }
+            _ => {
+                state.first_failure = Some(now);
+                state.consecutive_failures = 1;
+            }
+        }
+
+        if state.state == CircuitState::Closed
+            && state.consecutive_failures >= self.config.failure_threshold
+        {
+            log::error!(
+                "{} consecutive failures, circuit open for {:?}",
+                state.consecutive_failures,
+                self.config.cooldown
+            );
+            state.state = CircuitState::Open { since: now };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Creates a circuit breaker with the given cooldown and a threshold of 2 failures.
+    fn create_circuit_breaker(cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            window: Duration::from_secs(60),
+            cooldown,
+        })
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = create_circuit_breaker(Duration::from_secs(60));
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_success();
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.check().is_ok());
+
+        breaker.record_failure();
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+        assert!(matches!(
+            breaker.check(),
+            Err(Error::CircuitOpen { cooldown_remaining }) if cooldown_remaining > Duration::ZERO
+        ));
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = create_circuit_breaker(Duration::ZERO);
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+
+        // the cooldown elapsed, so a probe is allowed
+        assert!(breaker.check().is_ok());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        breaker.record_failure();
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+
+        assert!(breaker.check().is_ok());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::{Error, Result};
+
+/// Limits the number of concurrent HTTP requests of a client.
+/// Requests beyond the limit wait in a queue of limited depth.
+#[derive(Debug)]
+pub(crate) struct ConcurrencyLimiter {
+    semaphore: Semaphore,
+    max_concurrent: usize,
+    max_queue_depth: usize,
+    queued: AtomicUsize,
+}
+
+impl ConcurrencyLimiter {
+    /// Creates a new limiter.
+    ///
+    /// # Arguments
+    /// * `max_concurrent` - The maximum number of concurrent requests, at least 1.
+    /// * `max_queue_depth` - The maximum number of requests waiting for a permit.
+    pub(crate) fn new(max_concurrent: usize, max_queue_depth: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+
+        Self {
+            semaphore: Semaphore::new(max_concurrent),
+            max_concurrent,
+            max_queue_depth,
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the maximum number of concurrent requests.
+    pub(crate) fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Waits for a permit to send a request, which is released when the permit is dropped.
+    /// Returns `Error::QueueFull` if the maximum number of requests is already waiting.
+    pub(crate) async fn acquire(&self) -> Result<SemaphorePermit<'_>> {
+        if let Ok(permit) = self.semaphore.try_acquire() {
+            return Ok(permit);
+        }
+
+        let queued = self.queued.fetch_add(1, Ordering::AcqRel);
+        if queued >= self.max_queue_depth {
+            self.queued.fetch_sub(1, Ordering::AcqRel);
+            return Err(Error::QueueFull);
+        }
+
+        let permit = self.semaphore.acquire().await;
+        self.queued.fetch_sub(1, Ordering::AcqRel);
+
+        permit.map_err(|e| Error::InternalError(e.to_string()))
+    }
+}
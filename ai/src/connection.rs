@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// The configuration of the connection pool of the underlying HTTP client.
+#[derive(Debug, Clone)]
+pub struct ConnectionConfig {
+    /// The maximum number of idle connections kept open per host.
+    pub max_idle_per_host: usize,
+
+    /// The time after which idle connections are closed.
+    pub idle_timeout: Duration,
+
+    /// The interval of the TCP keepalive probes, disabled if None.
+    pub tcp_keepalive: Option<Duration>,
+
+    /// Whether to disable Nagle's algorithm, i.e., send small packets immediately.
+    pub tcp_nodelay: bool,
+}
+
+impl Default for ConnectionConfig {
+    /// Returns the defaults of `reqwest`.
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: usize::MAX,
+            idle_timeout: Duration::from_secs(90),
+            tcp_keepalive: None,
+            tcp_nodelay: true,
+        }
+    }
+}
+
+/// The default user agent of the requests.
+pub(crate) const DEFAULT_USER_AGENT: &str = concat!("rs-ai/", env!("CARGO_PKG_VERSION"));
+
+/// The settings the underlying HTTP client is built from.
+/// In the browser, only the user agent is applied, as the timeouts, connections and proxies
+/// are managed by the browser.
+#[derive(Debug, Clone)]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub(crate) struct HttpSettings {
+    pub(crate) timeout: Duration,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) proxy: Option<reqwest::Proxy>,
+    pub(crate) connection: ConnectionConfig,
+    pub(crate) user_agent: String,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            #[cfg(not(target_arch = "wasm32"))]
+            proxy: None,
+            connection: ConnectionConfig::default(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+}
+
+impl HttpSettings {
+    /// Builds the HTTP client with the settings.
+    pub(crate) fn build(&self) -> Result<reqwest::Client> {
+        let builder = reqwest::Client::builder().user_agent(&self.user_agent);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let builder = {
+            let mut builder = builder
+                .timeout(self.timeout)
+                .pool_max_idle_per_host(self.connection.max_idle_per_host)
+                .pool_idle_timeout(self.connection.idle_timeout)
+                .tcp_keepalive(self.connection.tcp_keepalive)
+                .tcp_nodelay(self.connection.tcp_nodelay);
+
+            if let Some(proxy) = &self.proxy {
+                builder = builder.proxy(proxy.clone());
+            }
+
+            builder
+        };
+
+        builder.build().map_err(|e| {
+            log::error!("Failed to create HTTP client: {}", e);
+            Error::HTTPError(Box::new(e))
+        })
+    }
+}
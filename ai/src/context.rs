@@ -0,0 +1,105 @@
+use crate::{Error, Message, Result, Role, budget};
+
+/// The strategy for pruning messages exceeding the context budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PruneStrategy {
+    /// Removes the earliest non-system messages first.
+    /// The model loses the context of the removed messages and may lose track of
+    /// earlier parts of the conversation.
+    #[default]
+    OldestFirst,
+}
+
+/// A limit for the estimated number of tokens of the messages sent to the model,
+/// e.g., the context length of the model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    /// The maximum number of tokens of the messages.
+    pub max_tokens: u64,
+
+    /// The strategy for pruning messages exceeding the budget.
+    pub prune_strategy: PruneStrategy,
+}
+
+impl ContextBudget {
+    /// Creates a new context budget pruning the oldest messages first.
+    ///
+    /// # Arguments
+    /// * `max_tokens` - The maximum number of tokens of the messages.
+    pub fn new(max_tokens: u64) -> Self {
+        Self {
+            max_tokens,
+            prune_strategy: PruneStrategy::default(),
+        }
+    }
+
+    /// Sets the strategy for pruning messages exceeding the budget.
+    ///
+    /// # Arguments
+    /// * `strategy` - The strategy for pruning messages.
+    pub fn with_prune_strategy(mut self, strategy: PruneStrategy) -> Self {
+        self.prune_strategy = strategy;
+        self
+    }
+
+    /// Roughly estimates the number of tokens of the given messages,
+    /// assuming four characters per token.
+    ///
+    /// # Arguments
+    /// * `messages` - The messages to estimate the number of tokens for.
+    pub fn estimate(messages: &[Message]) -> u64 {
+        budget::estimate_tokens(messages)
+    }
+
+    /// Checks that the estimated number of tokens of the given messages is within the budget.
+    ///
+    /// # Arguments
+    /// * `messages` - The messages to check.
+    pub fn check(&self, messages: &[Message]) -> Result<()> {
+        let estimated = Self::estimate(messages);
+        if estimated > self.max_tokens {
+            return Err(Error::ContextLengthExceeded {
+                max_tokens: Some(self.max_tokens),
+                used_tokens: Some(estimated),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Removes messages according to the prune strategy until the messages fit into the budget.
+    /// System messages are never removed.
+    /// Tool results directly following a removed message are removed as well, as they
+    /// would lose the tool call they belong to.
+    /// Returns the number of removed messages.
+    ///
+    /// # Arguments
+    /// * `messages` - The messages to prune.
+    pub fn prune_to_fit(&self, messages: &mut Vec<Message>) -> usize {
+        match self.prune_strategy {
+            PruneStrategy::OldestFirst => self.prune_oldest_first(messages),
+        }
+    }
+
+    /// Removes the oldest non-system messages until the messages fit into the budget.
+    /// Returns the number of removed messages.
+    ///
+    /// # Arguments
+    /// * `messages` - The messages to prune.
+    fn prune_oldest_first(&self, messages: &mut Vec<Message>) -> usize {
+        let num_messages = messages.len();
+
+        while Self::estimate(messages) > self.max_tokens {
+            let Some(index) = messages.iter().position(|m| m.role != Role::System) else {
+                break;
+            };
+
+            messages.remove(index);
+            while messages.get(index).is_some_and(|m| m.role == Role::Tool) {
+                messages.remove(index);
+            }
+        }
+
+        num_messages - messages.len()
+    }
+}
@@ -0,0 +1,271 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ChatCompletionParameter, ClientTrait, Error, FinishReason, JsonToolCall, Message, Result, Role,
+};
+
+/// The current version of the conversation file format.
+const CONVERSATION_FILE_VERSION: u32 = 1;
+
+/// The version of the legacy conversation files, which only contain the messages as JSON array.
+const LEGACY_CONVERSATION_FILE_VERSION: u32 = 0;
+
+/// The persisted state of a conversation.
+#[derive(Serialize, Deserialize)]
+struct ConversationFile {
+    version: u32,
+    model: String,
+
+    #[serde(default)]
+    system_prompt: Option<String>,
+
+    messages: Vec<Message>,
+
+    #[serde(default)]
+    pending_tool_calls: Vec<JsonToolCall>,
+}
+
+/// The response of the model to a single turn of a conversation.
+#[derive(Debug, Clone)]
+pub struct ConversationTurn {
+    /// The assistant message of the response.
+    pub message: Message,
+
+    /// The reason why the model stopped generating.
+    pub finish_reason: FinishReason,
+}
+
+impl ConversationTurn {
+    /// Returns the text content of the assistant message.
+    pub fn content(&self) -> String {
+        self.message.content.to_text()
+    }
+
+    /// Returns the tool calls requested by the model in this turn.
+    pub fn tool_calls(&self) -> &[JsonToolCall] {
+        &self.message.tool_calls
+    }
+}
+
+/// A multi-turn conversation keeping the message history and the tool calls of the model
+/// that still wait for their results.
+#[derive(Clone)]
+pub struct Conversation<'a> {
+    parameter: ChatCompletionParameter<'a>,
+    pending_tool_calls: Vec<JsonToolCall>,
+}
+
+impl<'a> Conversation<'a> {
+    /// Creates a new conversation continuing the messages of the given parameter.
+    ///
+    /// # Arguments
+    /// * `parameter` - The parameter for the chat completion requests of the conversation.
+    pub fn new(parameter: ChatCompletionParameter<'a>) -> Self {
+        Self {
+            parameter,
+            pending_tool_calls: Vec::new(),
+        }
+    }
+
+    /// Branches the conversation at its current state, e.g., for exploring alternative
+    /// continuations. The fork has the same model, message history, tools and sampling
+    /// parameters, but its state is independent of the original afterwards.
+    /// The fork does not hold a client, so the model cache is only shared if both
+    /// conversations are sent with the same client.
+    pub fn fork(&self) -> Conversation<'a> {
+        self.clone()
+    }
+
+    /// Returns the parameter for the chat completion requests of the conversation.
+    pub fn parameter(&self) -> &ChatCompletionParameter<'a> {
+        &self.parameter
+    }
+
+    /// Returns the parameter for changing the settings of the following requests.
+    pub fn parameter_mut(&mut self) -> &mut ChatCompletionParameter<'a> {
+        &mut self.parameter
+    }
+
+    /// Returns all messages of the conversation so far.
+    pub fn history(&self) -> &[Message] {
+        self.parameter.messages()
+    }
+
+    /// Returns the tool calls of the model that still wait for their results.
+    pub fn pending_tool_calls(&self) -> &[JsonToolCall] {
+        &self.pending_tool_calls
+    }
+
+    /// Returns true if the model requested tool calls whose results are not yet resolved.
+    pub fn is_waiting_for_tools(&self) -> bool {
+        !self.pending_tool_calls.is_empty()
+    }
+
+    /// Saves the model, the system prompt, the message history and the pending tool calls
+    /// as JSON to the given file. Other settings of the parameter, e.g., the tools, are not saved.
+    /// The file is written to a temporary file first and then renamed,
+    /// so that an interrupted write does not corrupt it.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut messages = self.history();
+        let system_prompt = match messages.first() {
+            Some(message) if message.role == Role::System => {
+                messages = &messages[1..];
+                Some(message.content.to_text())
+            }
+            _ => None,
+        };
+
+        let file = ConversationFile {
+            version: CONVERSATION_FILE_VERSION,
+            model: self.parameter.model().to_string(),
+            system_prompt,
+            messages: messages.to_vec(),
+            pending_tool_calls: self.pending_tool_calls.clone(),
+        };
+
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| Error::InternalError(format!("Failed to encode conversation: {}", e)))?;
+
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        std::fs::write(&tmp_path, json).map_err(|e| Error::IO(Box::new(e)))?;
+        std::fs::rename(&tmp_path, path).map_err(|e| Error::IO(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Loads a conversation saved with `save` from the given file.
+    /// Legacy files only containing the messages as JSON array are loaded as well. As they do not
+    /// contain the model, the model of the loaded conversation is empty and must be set by the caller.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    pub fn load(path: &Path) -> Result<Conversation<'static>> {
+        let json = std::fs::read_to_string(path).map_err(|e| Error::IO(Box::new(e)))?;
+        let invalid = |e: serde_json::Error| {
+            Error::Deserialization(format!("Invalid conversation {}: {}", path.display(), e))
+        };
+
+        let value: serde_json::Value = serde_json::from_str(&json).map_err(invalid)?;
+        let file = if value.is_array() {
+            ConversationFile {
+                version: LEGACY_CONVERSATION_FILE_VERSION,
+                model: String::new(),
+                system_prompt: None,
+                messages: serde_json::from_value(value).map_err(invalid)?,
+                pending_tool_calls: Vec::new(),
+            }
+        } else {
+            serde_json::from_value::<ConversationFile>(value).map_err(invalid)?
+        };
+
+        if file.version != CONVERSATION_FILE_VERSION
+            && file.version != LEGACY_CONVERSATION_FILE_VERSION
+        {
+            return Err(Error::Deserialization(format!(
+                "Unsupported conversation version {} in {}",
+                file.version,
+                path.display()
+            )));
+        }
+
+        let mut messages = Vec::with_capacity(file.messages.len() + 1);
+        if let Some(system_prompt) = file.system_prompt {
+            messages.push(Message::system(system_prompt));
+        }
+        messages.extend(file.messages);
+
+        Ok(Conversation {
+            parameter: ChatCompletionParameter::new(file.model, messages),
+            pending_tool_calls: file.pending_tool_calls,
+        })
+    }
+
+    /// Appends a user message with the given content and sends the conversation.
+    /// If the request fails, the user message is removed again, so that it can be repeated.
+    ///
+    /// # Arguments
+    /// * `client` - The client used for the chat completion request.
+    /// * `content` - The content of the user message.
+    pub async fn send_user<C: ClientTrait>(
+        &mut self,
+        client: &C,
+        content: &str,
+    ) -> Result<ConversationTurn> {
+        self.check_no_pending_tool_calls()?;
+
+        self.parameter.add_message(Message::user(content));
+
+        let turn = self.send(client).await;
+        if turn.is_err() {
+            let mut messages = self.parameter.messages().to_vec();
+            messages.pop();
+            self.parameter.set_messages(messages);
+        }
+
+        turn
+    }
+
+    /// Sends the conversation, e.g., after all tool calls have been resolved, and appends the
+    /// response of the model. The tool calls of the response become pending.
+    ///
+    /// # Arguments
+    /// * `client` - The client used for the chat completion request.
+    pub async fn send<C: ClientTrait>(&mut self, client: &C) -> Result<ConversationTurn> {
+        self.check_no_pending_tool_calls()?;
+
+        let choices = client.chat_completion(&self.parameter).await?;
+        let Some(choice) = choices.into_iter().next() else {
+            return Err(Error::InternalError(
+                "The response did not contain any choices".to_string(),
+            ));
+        };
+
+        self.pending_tool_calls = choice.message.tool_calls.clone();
+        self.parameter.add_message(choice.message.clone());
+
+        Ok(ConversationTurn {
+            message: choice.message,
+            finish_reason: choice.finish_reason,
+        })
+    }
+
+    /// Resolves the pending tool call with the given id by appending a tool message
+    /// with its result.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the pending tool call.
+    /// * `result` - The result of the tool call.
+    pub fn resolve_tool_call(&mut self, id: &str, result: &str) -> Result<()> {
+        let Some(index) = self
+            .pending_tool_calls
+            .iter()
+            .position(|call| call.id == id)
+        else {
+            return Err(Error::ToolNotFound(id.to_string()));
+        };
+
+        self.pending_tool_calls.remove(index);
+        self.parameter.add_message(Message::tool_result(id, result));
+
+        Ok(())
+    }
+
+    /// Fails if tool calls of the model still wait for their results.
+    fn check_no_pending_tool_calls(&self) -> Result<()> {
+        if self.is_waiting_for_tools() {
+            return Err(Error::InternalError(format!(
+                "{} tool calls are not resolved yet",
+                self.pending_tool_calls.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,93 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer};
 use thiserror::Error;
 
+use crate::Message;
+
+/// The keywords in the body of a bad request indicating that the context length was exceeded.
+const CONTEXT_LENGTH_KEYWORDS: [&str; 3] = ["context_length", "context window", "maximum context"];
+
+/// The keywords in the body of a bad request indicating a rejection by a safety system.
+const CONTENT_FILTER_KEYWORDS: [&str; 3] = ["content_filter", "content filter", "policy violation"];
+
+/// The phrases in the error message of a bad request followed by the maximum number of tokens.
+const MAX_TOKENS_PHRASES: [&str; 3] = [
+    "maximum context length is",
+    "context length of",
+    "context window of",
+];
+
+/// The phrases in the error message of a bad request followed by the number of used tokens.
+const USED_TOKENS_PHRASES: [&str; 2] = ["resulted in", "requested"];
+
+/// The optional word between a phrase and the number of tokens, e.g., "requested about 9021".
+const APPROXIMATION_WORD: &str = "about";
+
+/// The structured error of an unsuccessful API response, i.e., the body
+/// `{"error": {"message": ..., "type": ..., "code": ..., "param": ...}}`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ApiErrorBody {
+    /// The error message of the provider.
+    pub message: String,
+
+    /// The type of the error, e.g., `invalid_request_error`.
+    #[serde(rename = "type")]
+    pub error_type: Option<String>,
+
+    /// The error code, given either as string or as number.
+    #[serde(deserialize_with = "deserialize_code")]
+    pub code: Option<String>,
+
+    /// The parameter of the request causing the error.
+    pub param: Option<String>,
+
+    /// The HTTP status code of the response.
+    #[serde(skip)]
+    pub status: Option<StatusCode>,
+
+    /// The raw body of the response.
+    #[serde(skip)]
+    pub raw: Option<String>,
+}
+
+impl ApiErrorBody {
+    /// Parses the structured error from the given response body.
+    /// Returns `None` if the body does not contain a structured error.
+    ///
+    /// # Arguments
+    /// * `body` - The body of the unsuccessful response.
+    pub fn parse(body: &str) -> Option<Self> {
+        /// The envelope of the structured error.
+        #[derive(Deserialize)]
+        struct ApiErrorResponse {
+            error: ApiErrorBody,
+        }
+
+        let response: ApiErrorResponse = serde_json::from_str(body).ok()?;
+        Some(ApiErrorBody {
+            raw: Some(body.to_string()),
+            ..response.error
+        })
+    }
+}
+
+/// Deserializes an optional error code given either as string or as number.
+///
+/// # Arguments
+/// * `deserializer` - The deserializer of the error code.
+fn deserialize_code<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(code) => Some(code),
+        serde_json::Value::Number(code) => Some(code.to_string()),
+        _ => None,
+    })
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("IO Error: {0}")]
@@ -17,12 +105,369 @@ pub enum Error {
     #[error("Bad Request: {0}")]
     BadRequest(String),
 
+    #[error("API Error: {}", .0.message)]
+    ApiError(Box<ApiErrorBody>),
+
+    #[error("Rate limit exceeded, retry after: {retry_after:?}")]
+    RateLimit { retry_after: Option<Duration> },
+
     #[error("Tool not found: {0}")]
     ToolNotFound(String),
 
+    #[error("Duplicate tool name: {0}")]
+    DuplicateTool(String),
+
+    #[error("Tool {tool_name} timed out after {elapsed:?}")]
+    ToolTimeout {
+        tool_name: String,
+        elapsed: Duration,
+    },
+
     #[error("Deserialization Error: {0}")]
     Deserialization(String),
+
+    #[error("Stream Error: {0}")]
+    StreamError(String),
+
+    #[error("The stream was interrupted, last event id: {last_event_id:?}")]
+    StreamInterrupted { last_event_id: Option<String> },
+
+    #[error("The request was cancelled")]
+    Cancelled,
+
+    #[error("Circuit open, requests are rejected for another {cooldown_remaining:?}")]
+    CircuitOpen { cooldown_remaining: Duration },
+
+    #[error("The request queue is full")]
+    QueueFull,
+
+    #[error("Budget exceeded: {current:.6} USD of {limit:.6} USD")]
+    BudgetExceeded { limit: f64, current: f64 },
+
+    #[error("Missing template variable: {0}")]
+    MissingTemplateVariable(String),
+
+    #[error(
+        "Context length exceeded: {} tokens of {} tokens",
+        format_token_count(used_tokens),
+        format_token_count(max_tokens)
+    )]
+    ContextLengthExceeded {
+        max_tokens: Option<u64>,
+        used_tokens: Option<u64>,
+    },
+
+    #[error("The response does not match the schema: {}", errors.join("; "))]
+    SchemaValidationFailed {
+        content: String,
+        errors: Vec<String>,
+    },
+
+    #[error("Content filtered: {message}")]
+    ContentFiltered { message: String },
+
+    #[error("Maximum number of iterations reached after {} messages", history.len())]
+    MaxIterationsReached { history: Vec<Message> },
+}
+
+impl Error {
+    /// Returns the error for a bad request with the given response body, i.e.,
+    /// `Error::ContextLengthExceeded` if the body reports an exceeded context length,
+    /// `Error::ContentFiltered` if the request was blocked by a safety system,
+    /// `Error::ApiError` if the body contains a structured error and `Error::BadRequest` otherwise.
+    ///
+    /// # Arguments
+    /// * `body` - The body of the bad request response.
+    pub(crate) fn from_bad_request(body: String) -> Self {
+        let lowercase = body.to_lowercase();
+        let api_error = ApiErrorBody::parse(&body);
+
+        if CONTEXT_LENGTH_KEYWORDS
+            .iter()
+            .any(|keyword| lowercase.contains(keyword))
+        {
+            // only the message is parsed, as the body contains other numbers, e.g., the status code
+            let message = api_error.as_ref().map_or(lowercase.clone(), |api_error| {
+                api_error.message.to_lowercase()
+            });
+
+            return Error::ContextLengthExceeded {
+                max_tokens: parse_number_after(&message, &MAX_TOKENS_PHRASES),
+                used_tokens: parse_number_after(&message, &USED_TOKENS_PHRASES),
+            };
+        }
+
+        if CONTENT_FILTER_KEYWORDS
+            .iter()
+            .any(|keyword| lowercase.contains(keyword))
+        {
+            return Error::ContentFiltered {
+                message: api_error.map_or(body, |api_error| api_error.message),
+            };
+        }
+
+        match api_error {
+            Some(api_error) => Error::ApiError(Box::new(ApiErrorBody {
+                status: Some(StatusCode::BAD_REQUEST),
+                ..api_error
+            })),
+            None => Error::BadRequest(body),
+        }
+    }
+
+    /// Returns the error for an unsuccessful response with the given status code and body,
+    /// i.e., `Error::ApiError` if the body contains a structured error and
+    /// `Error::HTTPErrorWithStatusCode` otherwise.
+    ///
+    /// # Arguments
+    /// * `status` - The status code of the response.
+    /// * `body` - The body of the response.
+    pub(crate) fn from_status(status: StatusCode, body: &str) -> Self {
+        match ApiErrorBody::parse(body) {
+            Some(api_error) => Error::ApiError(Box::new(ApiErrorBody {
+                status: Some(status),
+                ..api_error
+            })),
+            None => Error::HTTPErrorWithStatusCode(status),
+        }
+    }
+
+    /// Returns true if the error is permanent and retrying the same request is pointless,
+    /// e.g., for invalid requests, authentication errors or an exceeded budget.
+    pub fn is_permanent(&self) -> bool {
+        match self {
+            Error::BadRequest(_)
+            | Error::ToolNotFound(_)
+            | Error::SchemaValidationFailed { .. }
+            | Error::BudgetExceeded { .. }
+            | Error::ContextLengthExceeded { .. }
+            | Error::ContentFiltered { .. } => true,
+            Error::ApiError(api_error) => api_error.status.is_none_or(|status| {
+                status == StatusCode::BAD_REQUEST || is_permanent_status(status)
+            }),
+            Error::HTTPErrorWithStatusCode(status) => is_permanent_status(*status),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the request was rejected by the safety system of the provider.
+    pub fn is_content_filtered(&self) -> bool {
+        matches!(self, Error::ContentFiltered { .. })
+    }
+
+    /// Returns true if the error is transient and the request may succeed when retried.
+    /// Permanent errors are never retryable.
+    pub fn is_retryable(&self) -> bool {
+        if self.is_permanent() {
+            return false;
+        }
+
+        match self {
+            Error::HTTPError(_) => true,
+            Error::RateLimit { .. } => true,
+            Error::ApiError(api_error) => api_error.status.is_some_and(is_retryable_status),
+            Error::HTTPErrorWithStatusCode(status) => is_retryable_status(*status),
+            _ => false,
+        }
+    }
+}
+
+/// Returns true if a response with the given status code fails permanently,
+/// i.e., for authentication errors.
+///
+/// # Arguments
+/// * `status` - The status code of the response.
+fn is_permanent_status(status: StatusCode) -> bool {
+    status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
+}
+
+/// Returns true if a response with the given status code may succeed when retried,
+/// i.e., for rate limits and server errors.
+///
+/// # Arguments
+/// * `status` - The status code of the response.
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Returns the given number of tokens as string or "unknown" if not available.
+///
+/// # Arguments
+/// * `tokens` - The optional number of tokens.
+fn format_token_count(tokens: &Option<u64>) -> String {
+    tokens.map_or_else(|| "unknown".to_string(), |tokens| tokens.to_string())
+}
+
+/// Returns the first number directly following one of the given phrases in the text, if any.
+/// Only whitespace and optionally the word "about" may separate the phrase and the number,
+/// so that unrelated numbers are never returned.
+///
+/// # Arguments
+/// * `text` - The text to search.
+/// * `phrases` - The phrases preceding the number.
+fn parse_number_after(text: &str, phrases: &[&str]) -> Option<u64> {
+    phrases.iter().find_map(|phrase| {
+        text.match_indices(phrase).find_map(|(start, _)| {
+            let rest = text[start + phrase.len()..].trim_start();
+            let rest = match rest.strip_prefix(APPROXIMATION_WORD) {
+                Some(stripped) if stripped.starts_with(char::is_whitespace) => {
+                    stripped.trim_start()
+                }
+                _ => rest,
+            };
+
+            let digits: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == ',')
+                .filter(char::is_ascii_digit)
+                .collect();
+
+            digits.parse().ok()
+        })
+    })
 }
 
 /// The result type used in this crate.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_bad_request() {
+        let body = r#"{"error":{"message":"This model's maximum context length is 8,192 tokens. However, your messages resulted in 9021 tokens.","code":"context_length_exceeded"}}"#;
+        assert!(matches!(
+            Error::from_bad_request(body.to_string()),
+            Error::ContextLengthExceeded {
+                max_tokens: Some(8192),
+                used_tokens: Some(9021)
+            }
+        ));
+
+        let body = "Input exceeds the Context Window of the model";
+        assert!(matches!(
+            Error::from_bad_request(body.to_string()),
+            Error::ContextLengthExceeded {
+                max_tokens: None,
+                used_tokens: None
+            }
+        ));
+
+        // the status code in the body is not the context length
+        let body =
+            r#"{"error":{"message":"Input exceeds the context window of the model","code":400}}"#;
+        assert!(matches!(
+            Error::from_bad_request(body.to_string()),
+            Error::ContextLengthExceeded {
+                max_tokens: None,
+                used_tokens: None
+            }
+        ));
+
+        let body = r#"{"error":{"message":"The context window of this model is 4096 tokens, you requested about 5,000 tokens","code":400}}"#;
+        assert!(matches!(
+            Error::from_bad_request(body.to_string()),
+            Error::ContextLengthExceeded {
+                max_tokens: None,
+                used_tokens: Some(5000)
+            }
+        ));
+
+        let body =
+            r#"{"error":{"message":"Your request was rejected by the content filter","code":400}}"#;
+        let error = Error::from_bad_request(body.to_string());
+        assert!(error.is_content_filtered());
+        assert!(matches!(
+            error,
+            Error::ContentFiltered { message } if message == "Your request was rejected by the content filter"
+        ));
+
+        let body = "Policy Violation detected";
+        assert!(matches!(
+            Error::from_bad_request(body.to_string()),
+            Error::ContentFiltered { message } if message == body
+        ));
+
+        let body = r#"{"error":{"message":"openai/gpt-5000 is not a valid model ID","type":"invalid_request_error","code":400,"param":"model"}}"#;
+        match Error::from_bad_request(body.to_string()) {
+            Error::ApiError(api_error) => {
+                assert_eq!(api_error.message, "openai/gpt-5000 is not a valid model ID");
+                assert_eq!(
+                    api_error.error_type.as_deref(),
+                    Some("invalid_request_error")
+                );
+                assert_eq!(api_error.code.as_deref(), Some("400"));
+                assert_eq!(api_error.param.as_deref(), Some("model"));
+                assert_eq!(api_error.status, Some(StatusCode::BAD_REQUEST));
+                assert_eq!(api_error.raw.as_deref(), Some(body));
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+
+        let body = "invalid model: openai/gpt-5000";
+        assert!(matches!(
+            Error::from_bad_request(body.to_string()),
+            Error::BadRequest(_)
+        ));
+    }
+
+    #[test]
+    fn test_from_status() {
+        let body =
+            r#"{"error":{"message":"Invalid API key","type":"authentication_error","code":401}}"#;
+        let error = Error::from_status(StatusCode::UNAUTHORIZED, body);
+        match &error {
+            Error::ApiError(api_error) => {
+                assert_eq!(api_error.message, "Invalid API key");
+                assert_eq!(api_error.code.as_deref(), Some("401"));
+                assert_eq!(api_error.status, Some(StatusCode::UNAUTHORIZED));
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        assert!(error.is_permanent());
+
+        let body = r#"{"error":{"message":"The upstream provider is overloaded","code":503}}"#;
+        let error = Error::from_status(StatusCode::SERVICE_UNAVAILABLE, body);
+        assert!(matches!(error, Error::ApiError(_)));
+        assert!(error.is_retryable());
+
+        let error = Error::from_status(StatusCode::BAD_GATEWAY, "<html>Bad Gateway</html>");
+        assert!(matches!(
+            error,
+            Error::HTTPErrorWithStatusCode(StatusCode::BAD_GATEWAY)
+        ));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(Error::RateLimit { retry_after: None }.is_retryable());
+        assert!(Error::HTTPErrorWithStatusCode(reqwest::StatusCode::BAD_GATEWAY).is_retryable());
+        assert!(
+            Error::HTTPErrorWithStatusCode(reqwest::StatusCode::TOO_MANY_REQUESTS).is_retryable()
+        );
+
+        assert!(!Error::HTTPErrorWithStatusCode(reqwest::StatusCode::NOT_FOUND).is_retryable());
+        assert!(!Error::BadRequest("invalid model".to_string()).is_retryable());
+        assert!(!Error::Cancelled.is_retryable());
+    }
+
+    #[test]
+    fn test_is_permanent() {
+        assert!(Error::BadRequest("invalid model".to_string()).is_permanent());
+        assert!(Error::ToolNotFound("get_weather".to_string()).is_permanent());
+        assert!(Error::HTTPErrorWithStatusCode(reqwest::StatusCode::UNAUTHORIZED).is_permanent());
+        assert!(Error::HTTPErrorWithStatusCode(reqwest::StatusCode::FORBIDDEN).is_permanent());
+        assert!(
+            Error::BudgetExceeded {
+                limit: 1.0,
+                current: 1.5
+            }
+            .is_permanent()
+        );
+
+        assert!(!Error::RateLimit { retry_after: None }.is_permanent());
+        assert!(!Error::HTTPErrorWithStatusCode(reqwest::StatusCode::BAD_GATEWAY).is_permanent());
+        assert!(!Error::Cancelled.is_permanent());
+    }
+}
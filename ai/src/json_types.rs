@@ -1,6 +1,11 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
 use schemars::Schema;
 use serde::{Deserialize, Serialize};
 
+use crate::{JsonPricing, PromptTemplate, Result};
+
 /// The request body used in the chat completion API
 #[derive(Serialize, Debug)]
 pub struct ChatCompletionRequest<'a, 'b, 'c, 'd> {
@@ -15,15 +20,97 @@ pub struct ChatCompletionRequest<'a, 'b, 'c, 'd> {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_format: Option<ResponseFormat<'c>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<&'a ProviderPreferences>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+}
+
+/// The preferences for routing the request to the providers on OpenRouter.
+/// Missing fields are deserialized with their default values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProviderPreferences {
+    /// The providers to try in the given order, e.g., `["OpenAI", "Azure"]`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+
+    /// Whether other providers may be used if the preferred ones are unavailable.
+    pub allow_fallbacks: bool,
+
+    /// Whether providers may store the data, either `"allow"` or `"deny"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<String>,
+
+    /// Whether only providers supporting all parameters of the request may be used.
+    pub require_parameters: bool,
+}
+
+impl Default for ProviderPreferences {
+    fn default() -> Self {
+        Self {
+            order: Vec::new(),
+            allow_fallbacks: true,
+            data_collection: None,
+            require_parameters: false,
+        }
+    }
 }
 
 #[derive(Serialize, Debug, Clone)]
 pub struct ResponseFormat<'a> {
     #[serde(rename = "type")]
     pub schema_type: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub json_schema: Option<&'a JsonSchemaDescription>,
 }
 
+impl ResponseFormat<'_> {
+    /// Creates the response format instructing the model to emit any valid JSON object
+    /// without a schema constraint.
+    pub fn json_object() -> ResponseFormat<'static> {
+        ResponseFormat {
+            schema_type: "json_object",
+            json_schema: None,
+        }
+    }
+
+    /// Returns true if this is the JSON object response format without a schema.
+    pub fn is_json_object(&self) -> bool {
+        self.schema_type == "json_object"
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct JsonSchemaDescription {
     pub name: String,
@@ -47,6 +134,17 @@ impl<'a, 'b> ChatCompletionRequest<'a, 'b, '_, '_> {
             tool_choice: None,
             response_format: None,
             tools: &EMPTY_TOOLS,
+            temperature: None,
+            max_tokens: None,
+            presence_penalty: None,
+            frequency_penalty: None,
+            seed: None,
+            n: None,
+            logprobs: None,
+            top_logprobs: None,
+            top_p: None,
+            provider: None,
+            stream: None,
         }
     }
 }
@@ -75,18 +173,90 @@ pub struct ChatCompletionResponse {
 }
 
 /// Represents the usage information in the chat completion response.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Usage {
     pub prompt_tokens: i64,
+
+    /// The number of generated tokens. Missing for embedding requests.
+    #[serde(default)]
     pub completion_tokens: i64,
+
     pub total_tokens: i64,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+}
+
+/// The breakdown of the prompt tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PromptTokensDetails {
+    /// The number of prompt tokens read from the prompt cache of the provider.
+    #[serde(default)]
+    pub cached_tokens: Option<i64>,
+}
+
+/// The breakdown of the generated tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CompletionTokensDetails {
+    /// The number of tokens the model used for reasoning before answering.
+    #[serde(default)]
+    pub reasoning_tokens: Option<i64>,
+}
+
+/// The role of the author of a message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+
+    /// Any role not known to this crate, kept for forward compatibility.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl Usage {
+    /// Returns the total cost of the request in USD using the given pricing.
+    ///
+    /// # Arguments
+    /// * `pricing` - The pricing of the model used for the request.
+    pub fn total_cost(&self, pricing: &JsonPricing) -> Result<f64> {
+        let prompt_cost = self.prompt_tokens as f64 * pricing.prompt_per_token()?;
+        let completion_cost = self.completion_tokens as f64 * pricing.completion_per_token()?;
+
+        Ok(prompt_cost + completion_cost)
+    }
+
+    /// Returns the number of prompt tokens read from the cache, if reported by the provider.
+    pub fn cached_tokens(&self) -> Option<i64> {
+        self.prompt_tokens_details
+            .as_ref()
+            .and_then(|details| details.cached_tokens)
+    }
+
+    /// Returns the number of reasoning tokens, if reported by the provider.
+    pub fn reasoning_tokens(&self) -> Option<i64> {
+        self.completion_tokens_details
+            .as_ref()
+            .and_then(|details| details.reasoning_tokens)
+    }
 }
 
 /// Represents a message in the chat completion request/response.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Message {
-    pub role: String,
-    pub content: String,
+    pub role: Role,
+
+    /// The content of the message, empty if the model refused or only requested tool calls.
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub content: MessageContent,
 
     #[serde(default)]
     #[serde(skip_serializing_if = "String::is_empty")]
@@ -95,6 +265,217 @@ pub struct Message {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tool_calls: Vec<JsonToolCall>,
+
+    /// The explanation of the model if it refused to answer the request.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refusal: Option<String>,
+
+    /// The reasoning of the model before the final answer, e.g., for reasoning models.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+}
+
+impl Message {
+    /// Creates a new system message with the given text, e.g., for standing instructions.
+    ///
+    /// # Arguments
+    /// * `content` - The text of the message.
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::text(Role::System, content.into())
+    }
+
+    /// Creates a new system message by rendering the given template.
+    ///
+    /// # Arguments
+    /// * `template` - The template of the system prompt.
+    /// * `vars` - The values of the template variables by their keys.
+    pub fn system_from_template(
+        template: &PromptTemplate,
+        vars: &HashMap<&str, &str>,
+    ) -> Result<Self> {
+        Ok(Self::system(template.render(vars)?))
+    }
+
+    /// Creates a new user message with the given text.
+    ///
+    /// # Arguments
+    /// * `content` - The text of the message.
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::text(Role::User, content.into())
+    }
+
+    /// Creates a new message with the given role and text.
+    ///
+    /// # Arguments
+    /// * `role` - The role of the message.
+    /// * `content` - The text of the message.
+    fn text(role: Role, content: String) -> Self {
+        Self {
+            role,
+            content: MessageContent::Text(content),
+            tool_call_id: String::new(),
+            tool_calls: Vec::new(),
+            refusal: None,
+            reasoning: None,
+        }
+    }
+
+    /// Returns true if the message contains a non-empty reasoning of the model.
+    pub fn has_reasoning(&self) -> bool {
+        self.reasoning.as_ref().is_some_and(|r| !r.is_empty())
+    }
+
+    /// Creates a new tool message with the result of the tool call with the given id.
+    ///
+    /// # Arguments
+    /// * `tool_call_id` - The id of the tool call the result belongs to.
+    /// * `content` - The result of the tool call.
+    pub fn tool_result(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Tool,
+            content: MessageContent::Text(content.into()),
+            tool_call_id: tool_call_id.into(),
+            tool_calls: Vec::new(),
+            refusal: None,
+            reasoning: None,
+        }
+    }
+}
+
+/// Deserializes the value and falls back to the default value if it is null.
+///
+/// # Arguments
+/// * `deserializer` - The deserializer to read the value from.
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// The content of a message, which is either plain text or a list of content parts.
+/// Plain text is serialized as JSON string, the parts as JSON array.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// Returns the text if the content is plain text.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            MessageContent::Text(text) => Some(text),
+            MessageContent::Parts(_) => None,
+        }
+    }
+
+    /// Returns the text of the content, concatenating all text parts.
+    pub fn to_text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    ContentPart::ImageUrl { .. } => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns true if the content has no text and no parts.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            MessageContent::Text(text) => text.is_empty(),
+            MessageContent::Parts(parts) => parts.is_empty(),
+        }
+    }
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Text(String::new())
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl From<&str> for MessageContent {
+    fn from(text: &str) -> Self {
+        MessageContent::Text(text.to_string())
+    }
+}
+
+impl From<Vec<ContentPart>> for MessageContent {
+    fn from(parts: Vec<ContentPart>) -> Self {
+        MessageContent::Parts(parts)
+    }
+}
+
+impl std::fmt::Display for MessageContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_text())
+    }
+}
+
+/// A single part of a multimodal message content.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "JsonContentPart", into = "JsonContentPart")]
+pub enum ContentPart {
+    /// A text part.
+    Text { text: String },
+
+    /// An image given by its URL, which can also be a base64 encoded data URL.
+    ImageUrl { url: String, detail: Option<String> },
+}
+
+/// The JSON representation of a content part.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonContentPart {
+    Text { text: String },
+    ImageUrl { image_url: JsonImageUrl },
+}
+
+/// The JSON representation of an image URL.
+#[derive(Serialize, Deserialize)]
+struct JsonImageUrl {
+    url: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl From<JsonContentPart> for ContentPart {
+    fn from(part: JsonContentPart) -> Self {
+        match part {
+            JsonContentPart::Text { text } => ContentPart::Text { text },
+            JsonContentPart::ImageUrl { image_url } => ContentPart::ImageUrl {
+                url: image_url.url,
+                detail: image_url.detail,
+            },
+        }
+    }
+}
+
+impl From<ContentPart> for JsonContentPart {
+    fn from(part: ContentPart) -> Self {
+        match part {
+            ContentPart::Text { text } => JsonContentPart::Text { text },
+            ContentPart::ImageUrl { url, detail } => JsonContentPart::ImageUrl {
+                image_url: JsonImageUrl { url, detail },
+            },
+        }
+    }
 }
 
 /// Represents a tool call in the message.
@@ -116,16 +497,116 @@ pub struct JsonFunctionCall {
 }
 
 /// Represents a single choice in the chat completion response.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Choice {
     pub index: i64,
-    pub finish_reason: String,
-    pub native_finish_reason: String,
+    pub finish_reason: FinishReason,
+    pub native_finish_reason: NativeFinishReason,
     pub message: Message,
+
+    #[serde(default)]
+    pub logprobs: Option<LogprobsContent>,
+}
+
+impl Choice {
+    /// Returns true if the model refused to answer the request.
+    pub fn is_refused(&self) -> bool {
+        self.message.refusal.is_some()
+    }
+
+    /// Returns the explanation of the model if it refused to answer the request.
+    pub fn refusal_message(&self) -> Option<&str> {
+        self.message.refusal.as_deref()
+    }
+}
+
+/// The normalized reason why the model stopped generating tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// The model reached a natural stopping point or a stop sequence.
+    Stop,
+
+    /// The model requested tool calls.
+    ToolCalls,
+
+    /// The maximum number of tokens was reached.
+    Length,
+
+    /// The content was omitted by a content filter.
+    ContentFilter,
+
+    /// Any finish reason not known to this crate, kept for forward compatibility.
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl FinishReason {
+    /// Returns the finish reason as string slice, as used by the API.
+    pub fn as_str(&self) -> &str {
+        match self {
+            FinishReason::Stop => "stop",
+            FinishReason::ToolCalls => "tool_calls",
+            FinishReason::Length => "length",
+            FinishReason::ContentFilter => "content_filter",
+            FinishReason::Unknown(reason) => reason,
+        }
+    }
+}
+
+/// The finish reason as reported by the provider, before the normalization into
+/// `FinishReason`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct NativeFinishReason(pub String);
+
+impl NativeFinishReason {
+    /// Returns the finish reason as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for NativeFinishReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The log probabilities of the tokens of a choice.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogprobsContent {
+    #[serde(default)]
+    pub content: Vec<TokenLogprob>,
+}
+
+/// The log probability of a single generated token.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+
+    /// The UTF-8 bytes of the token, if available.
+    #[serde(default)]
+    pub bytes: Option<Vec<u8>>,
+
+    /// The most likely tokens at this position.
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+/// One of the most likely tokens at a position.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+
+    #[serde(default)]
+    pub bytes: Option<Vec<u8>>,
 }
 
 /// Represents a tool used in the chat completion request.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JsonTool {
     /// The type of tool. Must be "function".
     #[serde(rename = "type")]
@@ -136,7 +617,7 @@ pub struct JsonTool {
 }
 
 /// The function definition for a tool.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JsonFunctionInfo {
     /// The name of the function.
     pub name: String,
@@ -149,6 +630,10 @@ pub struct JsonFunctionInfo {
 
     /// The parameters for the function.
     pub parameters: Schema,
+
+    /// The optional schema of the output of the function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Schema>,
 }
 
 /// Represents the choice of tool to be used in the chat completion request.
@@ -180,6 +665,111 @@ pub struct ToolChoiceFunctionDesc {
     pub name: String,
 }
 
+/// The request body used in the embeddings API
+#[derive(Serialize, Debug)]
+pub struct EmbeddingsRequest<'a, 'b> {
+    pub model: &'a str,
+    pub input: &'b [&'b str],
+    pub encoding_format: &'static str,
+}
+
+/// Represents the response from the embeddings API.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EmbeddingsResponse {
+    #[serde(default)]
+    pub model: String,
+
+    #[serde(default)]
+    pub object: String,
+
+    pub data: Vec<Embedding>,
+    pub usage: Usage,
+}
+
+/// Represents a single embedding in the embeddings response.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Embedding {
+    /// The index of the input text the embedding belongs to.
+    pub index: usize,
+
+    /// The embedding vector.
+    pub embedding: Vec<f32>,
+
+    #[serde(default)]
+    pub object: String,
+}
+
+/// The fence delimiting markdown code blocks.
+const CODE_FENCE: &str = "```";
+
+/// Returns the code blocks of the given markdown content as pairs of the language hint and the
+/// inner content. An unterminated code block extends to the end of the content.
+///
+/// # Arguments
+/// * `content` - The markdown content.
+fn code_blocks(content: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = content;
+
+    std::iter::from_fn(move || {
+        let start = rest.find(CODE_FENCE)? + CODE_FENCE.len();
+        let block = &rest[start..];
+
+        // the language hint is the remainder of the opening fence line
+        let (language, block) = match block.find('\n') {
+            Some(newline) => (block[..newline].trim(), &block[newline + 1..]),
+            None => ("", block),
+        };
+
+        let (inner, remainder) = match block.find(CODE_FENCE) {
+            Some(end) => (&block[..end], &block[end + CODE_FENCE.len()..]),
+            None => (block, ""),
+        };
+
+        rest = remainder;
+        Some((language, inner.trim()))
+    })
+}
+
+/// Returns the content of the first markdown code block with the given language hint,
+/// e.g., `rust` for a block starting with ` ```rust `. The language is compared case-insensitive.
+///
+/// # Arguments
+/// * `content` - The markdown content.
+/// * `language` - The language hint of the code block.
+pub fn extract_code_block<'a>(content: &'a str, language: &str) -> Option<&'a str> {
+    code_blocks(content)
+        .find(|(hint, _)| hint.eq_ignore_ascii_case(language))
+        .map(|(_, inner)| inner)
+}
+
+/// Strips markdown code fences, e.g., ` ```json ... ``` `, which models frequently wrap around
+/// JSON output. Content which already is valid JSON is returned trimmed, as JSON strings may
+/// contain code fences themselves. Otherwise, fences are only stripped if the content starts
+/// with one, preferring a block with the `json` language hint over the first code block.
+/// Returns the unchanged content if nothing applies.
+///
+/// # Arguments
+/// * `content` - The content of the response.
+pub fn extract_json(content: &str) -> Cow<'_, str> {
+    let trimmed = content.trim();
+    if serde_json::from_str::<serde::de::IgnoredAny>(trimmed).is_ok() {
+        return Cow::Borrowed(trimmed);
+    }
+
+    if !trimmed.starts_with(CODE_FENCE) {
+        return Cow::Borrowed(content);
+    }
+
+    if let Some(json) = extract_code_block(trimmed, "json") {
+        return Cow::Borrowed(json);
+    }
+
+    match code_blocks(trimmed).next() {
+        Some((_, inner)) => Cow::Borrowed(inner),
+        None => Cow::Borrowed(content),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -220,6 +810,233 @@ mod test {
 
         let response: ChatCompletionResponse = serde_json::from_str(json).unwrap();
         assert_eq!(response.id, "gen-1747167300-Qc7IgPZUPoopdSABk5KA");
+        assert!(!response.choices[0].is_refused());
+        assert!(!response.choices[0].message.has_reasoning());
+        assert_eq!(response.usage.reasoning_tokens(), Some(0));
+        assert_eq!(response.usage.cached_tokens(), Some(0));
+    }
+
+    #[test]
+    fn test_decoding_usage_details() {
+        let usage: Usage = serde_json::from_str(
+            r#"{
+                "prompt_tokens": 2048,
+                "completion_tokens": 10,
+                "total_tokens": 2058,
+                "prompt_tokens_details": { "cached_tokens": 1920 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(usage.cached_tokens(), Some(1920));
+        assert_eq!(usage.reasoning_tokens(), None);
+
+        let usage: Usage = serde_json::from_str(
+            r#"{
+                "prompt_tokens": 5,
+                "total_tokens": 5,
+                "prompt_tokens_details": {}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(usage.cached_tokens(), None);
+    }
+
+    #[test]
+    fn test_decoding_reasoning_response() {
+        let json = r#"
+        {
+            "id": "gen-1747167500-ReasoningExample",
+            "provider": "DeepSeek",
+            "model": "deepseek/deepseek-r1",
+            "object": "chat.completion",
+            "created": 1747167500,
+            "choices": [
+                {
+                "finish_reason": "stop",
+                "native_finish_reason": "stop",
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "4",
+                    "reasoning": "The user asks for 2 + 2, which is 4."
+                }
+                }
+            ],
+            "usage": {
+                "prompt_tokens": 12,
+                "completion_tokens": 30,
+                "total_tokens": 42,
+                "completion_tokens_details": { "reasoning_tokens": 28 }
+            }
+            }
+        "#;
+
+        let response: ChatCompletionResponse = serde_json::from_str(json).unwrap();
+        let message = &response.choices[0].message;
+
+        assert!(message.has_reasoning());
+        assert_eq!(
+            message.reasoning.as_deref(),
+            Some("The user asks for 2 + 2, which is 4.")
+        );
+        assert_eq!(response.usage.reasoning_tokens(), Some(28));
+    }
+
+    #[test]
+    fn test_decoding_refusal_response() {
+        let json = r#"
+        {
+            "id": "gen-1747167400-RefusalExample",
+            "provider": "OpenAI",
+            "model": "openai/gpt-4o",
+            "object": "chat.completion",
+            "created": 1747167400,
+            "choices": [
+                {
+                "logprobs": null,
+                "finish_reason": "stop",
+                "native_finish_reason": "stop",
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "refusal": "I'm sorry, I can't help with that request.",
+                    "reasoning": null
+                }
+                }
+            ],
+            "usage": {
+                "prompt_tokens": 21,
+                "completion_tokens": 10,
+                "total_tokens": 31
+            }
+            }
+        "#;
+
+        let response: ChatCompletionResponse = serde_json::from_str(json).unwrap();
+        let choice = &response.choices[0];
+
+        assert!(choice.is_refused());
+        assert_eq!(
+            choice.refusal_message(),
+            Some("I'm sorry, I can't help with that request.")
+        );
+        assert!(choice.message.content.is_empty());
+    }
+
+    #[test]
+    fn test_decoding_logprobs_response() {
+        let json = include_str!("../test_data/logprobs_response.json");
+        let response: ChatCompletionResponse = serde_json::from_str(json).unwrap();
+
+        let logprobs = response.choices[0].logprobs.as_ref().unwrap();
+        assert_eq!(logprobs.content.len(), 2);
+
+        let token = &logprobs.content[0];
+        assert_eq!(token.token, "Yes");
+        assert_eq!(token.logprob, -0.0012);
+        assert_eq!(token.bytes.as_deref(), Some("Yes".as_bytes()));
+        assert_eq!(token.top_logprobs.len(), 2);
+        assert_eq!(token.top_logprobs[1].token, "No");
+
+        assert!(logprobs.content[1].bytes.is_none());
+    }
+
+    #[test]
+    fn test_finish_reason() {
+        let reasons: Vec<FinishReason> =
+            serde_json::from_str(r#"["stop", "tool_calls", "length", "content_filter", "error"]"#)
+                .unwrap();
+
+        assert_eq!(
+            reasons,
+            vec![
+                FinishReason::Stop,
+                FinishReason::ToolCalls,
+                FinishReason::Length,
+                FinishReason::ContentFilter,
+                FinishReason::Unknown("error".to_string()),
+            ]
+        );
+        assert_eq!(reasons[1].as_str(), "tool_calls");
+        assert_eq!(reasons[4].as_str(), "error");
+
+        let native: NativeFinishReason = serde_json::from_str(r#""end_turn""#).unwrap();
+        assert_eq!(native.as_str(), "end_turn");
+    }
+
+    #[test]
+    fn test_usage_total_cost() {
+        let usage = Usage {
+            prompt_tokens: 1000,
+            completion_tokens: 500,
+            total_tokens: 1500,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
+        };
+
+        let pricing: JsonPricing = serde_json::from_str(
+            r#"{"prompt": "0.000002", "completion": "0.000008", "request": null, "image": null,
+            "web_search": null, "internal_reasoning": null, "input_cache_read": null,
+            "input_cache_write": null}"#,
+        )
+        .unwrap();
+
+        let cost = usage.total_cost(&pricing).unwrap();
+        assert!((cost - 0.006).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_decoding_embeddings_response() {
+        let json = r#"
+        {
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 0, "embedding": [0.1, -0.2, 0.3] },
+                { "object": "embedding", "index": 1, "embedding": [0.4, 0.5, -0.6] }
+            ],
+            "model": "openai/text-embedding-3-small",
+            "usage": { "prompt_tokens": 8, "total_tokens": 8 }
+        }
+        "#;
+
+        let response: EmbeddingsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.data.len(), 2);
+        assert_eq!(response.data[1].index, 1);
+        assert_eq!(response.data[1].embedding, vec![0.4, 0.5, -0.6]);
+        assert_eq!(response.usage.prompt_tokens, 8);
+        assert_eq!(response.usage.completion_tokens, 0);
+    }
+
+    #[test]
+    fn test_message_content_serialization() {
+        let content = MessageContent::Text("Hello".to_string());
+        assert_eq!(serde_json::to_string(&content).unwrap(), r#""Hello""#);
+
+        let content = MessageContent::Parts(vec![
+            ContentPart::Text {
+                text: "What is in this image?".to_string(),
+            },
+            ContentPart::ImageUrl {
+                url: "https://example.com/image.png".to_string(),
+                detail: Some("low".to_string()),
+            },
+        ]);
+
+        let json = serde_json::to_string(&content).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"type":"text","text":"What is in this image?"},{"type":"image_url","image_url":{"url":"https://example.com/image.png","detail":"low"}}]"#
+        );
+
+        let decoded: MessageContent = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, content);
+        assert_eq!(decoded.to_text(), "What is in this image?");
+
+        let decoded: MessageContent = serde_json::from_str(r#""Hello""#).unwrap();
+        assert_eq!(decoded.as_text(), Some("Hello"));
     }
 
     #[derive(Serialize, Deserialize, Debug)]
@@ -238,6 +1055,36 @@ mod test {
         assert_eq!(json, r#"{"tool_choice":"required"}"#,);
     }
 
+    #[test]
+    fn test_decoding_provider_preferences() {
+        let preferences: ProviderPreferences =
+            serde_json::from_str(r#"{"order": ["OpenAI"]}"#).unwrap();
+        assert_eq!(
+            preferences,
+            ProviderPreferences {
+                order: vec!["OpenAI".to_string()],
+                ..Default::default()
+            }
+        );
+        assert!(preferences.allow_fallbacks);
+
+        let preferences: ProviderPreferences = serde_json::from_str("{}").unwrap();
+        assert_eq!(preferences, ProviderPreferences::default());
+    }
+
+    #[test]
+    fn test_role_serialization() {
+        assert_eq!(serde_json::to_string(&Role::User).unwrap(), r#""user""#);
+        assert_eq!(serde_json::to_string(&Role::Tool).unwrap(), r#""tool""#);
+
+        let role: Role = serde_json::from_str(r#""assistant""#).unwrap();
+        assert_eq!(role, Role::Assistant);
+
+        let role: Role = serde_json::from_str(r#""developer""#).unwrap();
+        assert_eq!(role, Role::Unknown("developer".to_string()));
+        assert_eq!(serde_json::to_string(&role).unwrap(), r#""developer""#);
+    }
+
     #[test]
     fn test_encoding_tool_choice_function() {
         let tool_choice = ToolChoice::Function(ToolChoiceFunction {
@@ -253,4 +1100,38 @@ mod test {
             r#"{"tool_choice":{"type":"function","function":{"name":"get_weather"}}}"#,
         );
     }
+
+    #[test]
+    fn test_extract_json() {
+        let json = r#"{"a": 1}"#;
+        assert!(matches!(extract_json(json), Cow::Borrowed(s) if s == json));
+
+        let padded = "\n  {\"a\": 1}  \n";
+        assert_eq!(extract_json(padded), json);
+
+        // code fences inside of JSON strings are not stripped
+        let nested = r#"{"code": "```rust\nfn main() {}\n```"}"#;
+        assert_eq!(extract_json(nested), nested);
+
+        let prose = "Here you go:\n```json\n  {\"a\": 1}\n```\nDone.";
+        assert_eq!(extract_json(prose), prose);
+
+        let fenced = " ```json\n  {\"a\": 1}\n```\n";
+        assert_eq!(extract_json(fenced), json);
+
+        let fenced = "```\n{\"a\": 1}\n```";
+        assert_eq!(extract_json(fenced), json);
+
+        let fenced = "```text\nignored\n```\n``` JSON \n{\"a\": 1}";
+        assert_eq!(extract_json(fenced), json);
+    }
+
+    #[test]
+    fn test_extract_code_block() {
+        let content = "```python\nprint(1)\n```\n\n```rust\nfn main() {}\n```";
+        assert_eq!(extract_code_block(content, "rust"), Some("fn main() {}"));
+        assert_eq!(extract_code_block(content, "Python"), Some("print(1)"));
+        assert_eq!(extract_code_block(content, "json"), None);
+        assert_eq!(extract_code_block("no code", "rust"), None);
+    }
 }
@@ -0,0 +1,184 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::platform::Instant;
+
+/// The strategy for selecting the API key of the next request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationStrategy {
+    /// Uses the keys one after another.
+    RoundRobin,
+
+    /// Uses a random key.
+    Random,
+
+    /// Uses the key that has not been used for the longest time.
+    LeastRecentlyUsed,
+}
+
+/// The usage state of a single API key.
+struct KeyState {
+    key: String,
+    last_used: Option<Instant>,
+    excluded_until: Option<Instant>,
+}
+
+/// The API keys of a client, which are rotated according to the strategy.
+/// Keys rejected by the API are excluded for a cooldown period.
+pub(crate) struct KeyPool {
+    keys: Mutex<Vec<KeyState>>,
+    strategy: RotationStrategy,
+    next_index: AtomicUsize,
+    key_cooldown: Duration,
+}
+
+impl KeyPool {
+    /// Creates a new key pool.
+    ///
+    /// # Arguments
+    /// * `keys` - The API keys, must not be empty.
+    /// * `strategy` - The strategy for selecting the keys.
+    /// * `key_cooldown` - The time a rejected key is excluded.
+    pub(crate) fn new(
+        keys: Vec<String>,
+        strategy: RotationStrategy,
+        key_cooldown: Duration,
+    ) -> Self {
+        let keys = keys
+            .into_iter()
+            .map(|key| KeyState {
+                key,
+                last_used: None,
+                excluded_until: None,
+            })
+            .collect();
+
+        Self {
+            keys: Mutex::new(keys),
+            strategy,
+            next_index: AtomicUsize::new(0),
+            key_cooldown,
+        }
+    }
+
+    /// Returns a copy of the pool with the given cooldown for rejected keys.
+    ///
+    /// # Arguments
+    /// * `key_cooldown` - The time a rejected key is excluded.
+    pub(crate) fn with_key_cooldown(&self, key_cooldown: Duration) -> Self {
+        let keys = self.keys.lock().unwrap();
+        Self::new(
+            keys.iter().map(|state| state.key.clone()).collect(),
+            self.strategy,
+            key_cooldown,
+        )
+    }
+
+    /// Selects the key for the next request.
+    /// If all keys are excluded, the exclusion is ignored.
+    pub(crate) fn next_key(&self) -> String {
+        let mut keys = self.keys.lock().unwrap();
+        let now = Instant::now();
+
+        let mut candidates: Vec<usize> = (0..keys.len())
+            .filter(|&i| keys[i].excluded_until.is_none_or(|until| until <= now))
+            .collect();
+        if candidates.is_empty() {
+            log::warn!("All API keys are excluded, using them anyway");
+            candidates = (0..keys.len()).collect();
+        }
+
+        let index = match self.strategy {
+            RotationStrategy::RoundRobin => {
+                let next = self.next_index.fetch_add(1, Ordering::Relaxed);
+                candidates[next % candidates.len()]
+            }
+            RotationStrategy::Random => candidates[rand::rng().random_range(0..candidates.len())],
+            // keys that have never been used come first, as None is less than Some
+            RotationStrategy::LeastRecentlyUsed => *candidates
+                .iter()
+                .min_by_key(|&&i| keys[i].last_used)
+                .unwrap(),
+        };
+
+        keys[index].last_used = Some(now);
+        keys[index].key.clone()
+    }
+
+    /// Excludes the given key for the cooldown period, e.g., after the API rejected it.
+    ///
+    /// # Arguments
+    /// * `key` - The rejected key.
+    pub(crate) fn exclude(&self, key: &str) {
+        let mut keys = self.keys.lock().unwrap();
+
+        if let Some(state) = keys.iter_mut().find(|state| state.key == key) {
+            log::error!(
+                "API key {} was rejected, excluding it for {:?}",
+                mask_key(key),
+                self.key_cooldown
+            );
+            state.excluded_until = Some(Instant::now() + self.key_cooldown);
+        }
+    }
+}
+
+/// Masks the given key for logging, only keeping the last four characters.
+///
+/// # Arguments
+/// * `key` - The key to mask.
+fn mask_key(key: &str) -> String {
+    let suffix: String = key
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    format!("***{}", suffix)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Creates a pool with the keys "a", "b" and "c".
+    fn create_pool(strategy: RotationStrategy) -> KeyPool {
+        let keys = ["a", "b", "c"].iter().map(|key| key.to_string()).collect();
+        KeyPool::new(keys, strategy, Duration::from_secs(60))
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let pool = create_pool(RotationStrategy::RoundRobin);
+        let keys: Vec<String> = (0..4).map(|_| pool.next_key()).collect();
+        assert_eq!(keys, ["a", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn test_least_recently_used() {
+        let pool = create_pool(RotationStrategy::LeastRecentlyUsed);
+        let keys: Vec<String> = (0..3).map(|_| pool.next_key()).collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+        assert_eq!(pool.next_key(), "a");
+    }
+
+    #[test]
+    fn test_exclude() {
+        let pool = create_pool(RotationStrategy::Random);
+        pool.exclude("a");
+        pool.exclude("b");
+        assert!((0..10).all(|_| pool.next_key() == "c"));
+
+        // all keys excluded, so they are used anyway
+        pool.exclude("c");
+        assert!(["a", "b", "c"].contains(&pool.next_key().as_str()));
+
+        assert_eq!(mask_key("sk-secret"), "***cret");
+    }
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use prometheus::{CounterVec, HistogramOpts, HistogramVec, Opts, Registry};
+
+use crate::{ChatCompletionResponse, Error, Result};
+
+/// The Prometheus collectors updated after each chat completion request.
+pub(crate) struct Metrics {
+    requests: CounterVec,
+    request_duration: HistogramVec,
+    prompt_tokens: CounterVec,
+    completion_tokens: CounterVec,
+}
+
+impl Metrics {
+    /// Creates the collectors and registers them at the given registry.
+    /// Fails if a collector cannot be registered, e.g., as it is already registered
+    /// by another client.
+    ///
+    /// # Arguments
+    /// * `registry` - The registry to register the collectors at.
+    pub(crate) fn new(registry: &Registry) -> Result<Self> {
+        let metrics = Self {
+            requests: CounterVec::new(
+                Opts::new(
+                    "rs_ai_requests_total",
+                    "The number of chat completion requests",
+                ),
+                &["model", "status"],
+            )
+            .map_err(prometheus_error)?,
+            request_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "rs_ai_request_duration_seconds",
+                    "The duration of the chat completion requests in seconds",
+                ),
+                &["model"],
+            )
+            .map_err(prometheus_error)?,
+            prompt_tokens: CounterVec::new(
+                Opts::new("rs_ai_prompt_tokens_total", "The number of prompt tokens"),
+                &["model"],
+            )
+            .map_err(prometheus_error)?,
+            completion_tokens: CounterVec::new(
+                Opts::new(
+                    "rs_ai_completion_tokens_total",
+                    "The number of completion tokens",
+                ),
+                &["model"],
+            )
+            .map_err(prometheus_error)?,
+        };
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 4] = [
+            Box::new(metrics.requests.clone()),
+            Box::new(metrics.request_duration.clone()),
+            Box::new(metrics.prompt_tokens.clone()),
+            Box::new(metrics.completion_tokens.clone()),
+        ];
+
+        for collector in collectors {
+            registry.register(collector).map_err(prometheus_error)?;
+        }
+
+        Ok(metrics)
+    }
+
+    /// Records the result of a chat completion request.
+    ///
+    /// # Arguments
+    /// * `model` - The model of the request.
+    /// * `duration` - The duration of the request including all retries.
+    /// * `response` - The result of the request.
+    pub(crate) fn record(
+        &self,
+        model: &str,
+        duration: Duration,
+        response: &Result<ChatCompletionResponse>,
+    ) {
+        let status = if response.is_ok() { "success" } else { "error" };
+        self.requests.with_label_values(&[model, status]).inc();
+        self.request_duration
+            .with_label_values(&[model])
+            .observe(duration.as_secs_f64());
+
+        if let Ok(response) = response {
+            self.prompt_tokens
+                .with_label_values(&[model])
+                .inc_by(response.usage.prompt_tokens.max(0) as f64);
+            self.completion_tokens
+                .with_label_values(&[model])
+                .inc_by(response.usage.completion_tokens.max(0) as f64);
+        }
+    }
+}
+
+/// Converts the given Prometheus error into the error of this crate.
+///
+/// # Arguments
+/// * `error` - The Prometheus error.
+fn prometheus_error(error: prometheus::Error) -> Error {
+    Error::InternalError(format!("Failed to register Prometheus metrics: {}", error))
+}
@@ -0,0 +1,159 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode, Url};
+
+use crate::platform::Instant;
+use crate::{Error, Result};
+
+/// The future returned by middlewares and by the remaining chain.
+#[cfg(not(target_arch = "wasm32"))]
+pub type MiddlewareFuture<'a> = Pin<Box<dyn Future<Output = Result<RawResponse>> + Send + 'a>>;
+
+/// The future returned by middlewares and by the remaining chain.
+/// It is not `Send` on wasm32, as the fetch API of the browser is bound to its thread.
+#[cfg(target_arch = "wasm32")]
+pub type MiddlewareFuture<'a> = Pin<Box<dyn Future<Output = Result<RawResponse>> + 'a>>;
+
+/// A HTTP request that is about to be sent.
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Vec<u8>>,
+
+    /// The timeout of the request, overriding the default timeout of the client.
+    pub timeout: Option<Duration>,
+}
+
+impl PreparedRequest {
+    /// Creates a prepared request from the given request.
+    ///
+    /// # Arguments
+    /// * `request` - The request to convert.
+    pub(crate) fn from_request(request: reqwest::Request) -> Self {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| bytes.to_vec());
+
+        Self {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            headers: request.headers().clone(),
+            body,
+            timeout: request.timeout().copied(),
+        }
+    }
+
+    /// Converts the prepared request into a request that can be executed.
+    pub(crate) fn into_request(self) -> reqwest::Request {
+        let mut request = reqwest::Request::new(self.method, self.url);
+        *request.headers_mut() = self.headers;
+        *request.body_mut() = self.body.map(reqwest::Body::from);
+        *request.timeout_mut() = self.timeout;
+
+        request
+    }
+}
+
+/// The HTTP response to a request, with the body read completely.
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    /// Reads the given response completely.
+    ///
+    /// # Arguments
+    /// * `response` - The response to read.
+    pub(crate) async fn from_response(response: reqwest::Response) -> Result<Self> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(|e| {
+            log::error!("Failed to read response body: {}", e);
+            Error::HTTPError(Box::new(e))
+        })?;
+
+        Ok(Self {
+            status,
+            headers,
+            body: body.to_vec(),
+        })
+    }
+
+    /// Returns the body of the response as text.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// The remaining chain of middlewares, ending with sending the request.
+pub trait Next: Send + Sync {
+    /// Passes the request on to the next middleware or sends it.
+    ///
+    /// # Arguments
+    /// * `request` - The request to pass on.
+    fn run(&self, request: PreparedRequest) -> MiddlewareFuture<'_>;
+}
+
+/// A middleware intercepting all HTTP requests of the client, e.g., for logging, metrics,
+/// or signing requests.
+pub trait Middleware: Send + Sync {
+    /// Handles the given request. The middleware may modify the request and the response
+    /// or answer the request itself without calling `next`.
+    ///
+    /// # Arguments
+    /// * `request` - The request to handle.
+    /// * `next` - The remaining chain, which must be called to send the request.
+    fn handle<'a>(&'a self, request: PreparedRequest, next: &'a dyn Next) -> MiddlewareFuture<'a>;
+}
+
+/// Returns true if the value of the header with the given name must not be logged or recorded.
+///
+/// # Arguments
+/// * `name` - The name of the header.
+pub(crate) fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "authorization" || name.contains("key") || name.contains("token")
+}
+
+/// A middleware logging the method, URL, status and duration of each request.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LoggingMiddleware;
+
+impl Middleware for LoggingMiddleware {
+    fn handle<'a>(&'a self, request: PreparedRequest, next: &'a dyn Next) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            let method = request.method.clone();
+            let url = request.url.clone();
+            let start = Instant::now();
+
+            let result = next.run(request).await;
+            match &result {
+                Ok(response) => log::info!(
+                    "{} {} -> {} in {:?}",
+                    method,
+                    url,
+                    response.status,
+                    start.elapsed()
+                ),
+                Err(err) => log::warn!(
+                    "{} {} failed in {:?}: {}",
+                    method,
+                    url,
+                    start.elapsed(),
+                    err
+                ),
+            }
+
+            result
+        })
+    }
+}
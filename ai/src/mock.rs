@@ -0,0 +1,107 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    ChatCompletionParameter, Choice, ClientTrait, Error, FinishReason, JsonModels, LLMModels,
+    Message, MessageContent, NativeFinishReason, Result, Role,
+};
+
+/// The handler creating the response of the mock client for a request.
+type MockHandler = Box<dyn Fn(&ChatCompletionParameter<'_>) -> Result<Vec<Choice>> + Send + Sync>;
+
+/// A client returning pre-canned responses without any HTTP requests, intended for tests.
+/// The bodies of all received requests are captured for later assertions.
+pub struct MockClient {
+    handler: MockHandler,
+    models: Arc<LLMModels>,
+    requests: Mutex<Vec<serde_json::Value>>,
+}
+
+impl Default for MockClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClient {
+    /// Creates a new mock client without any models, which fails on every request
+    /// until a handler is set via `on_request`.
+    pub fn new() -> Self {
+        Self {
+            handler: Box::new(|_| {
+                Err(Error::InternalError(
+                    "No response configured for the mock client".to_string(),
+                ))
+            }),
+            models: Arc::new(LLMModels::new(JsonModels {
+                models: Vec::new(),
+                has_more: false,
+                next_cursor: None,
+            })),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sets the handler creating the response for each request.
+    ///
+    /// # Arguments
+    /// * `handler` - The handler getting the parameter of the request.
+    pub fn on_request<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&ChatCompletionParameter<'_>) -> Result<Vec<Choice>> + Send + Sync + 'static,
+    {
+        self.handler = Box::new(handler);
+        self
+    }
+
+    /// Sets the models returned by `get_models`.
+    ///
+    /// # Arguments
+    /// * `models` - The models to return.
+    pub fn with_models(mut self, models: LLMModels) -> Self {
+        self.models = Arc::new(models);
+        self
+    }
+
+    /// Returns the JSON bodies of all requests received so far.
+    pub fn requests(&self) -> Vec<serde_json::Value> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl ClientTrait for MockClient {
+    async fn chat_completion(
+        &self,
+        parameter: &ChatCompletionParameter<'_>,
+    ) -> Result<Vec<Choice>> {
+        let request_body = serde_json::to_value(parameter.to_request())
+            .map_err(|e| Error::InternalError(e.to_string()))?;
+        self.requests.lock().unwrap().push(request_body);
+
+        (self.handler)(parameter)
+    }
+
+    async fn get_models(&self) -> Result<Arc<LLMModels>> {
+        Ok(self.models.clone())
+    }
+}
+
+/// Creates a choice with an assistant message with the given content, e.g., for mock responses.
+///
+/// # Arguments
+/// * `content` - The content of the assistant message.
+pub fn make_choice(content: &str) -> Choice {
+    Choice {
+        index: 0,
+        finish_reason: FinishReason::Stop,
+        native_finish_reason: NativeFinishReason("stop".to_string()),
+        message: Message {
+            role: Role::Assistant,
+            content: MessageContent::Text(content.to_string()),
+            tool_call_id: String::new(),
+            tool_calls: Vec::new(),
+            refusal: None,
+            reasoning: None,
+        },
+        logprobs: None,
+    }
+}
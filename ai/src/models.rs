@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
+use crate::{Error, Result, Usage};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JsonArchitecture {
     pub modality: String,
@@ -10,10 +13,16 @@ pub struct JsonArchitecture {
     pub instruct_type: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonPricing {
-    pub prompt: String,
-    pub completion: String,
+    /// The price in USD per prompt token, NaN if the price is missing or invalid.
+    #[serde(default = "unknown_price", deserialize_with = "deserialize_price")]
+    pub prompt: f64,
+
+    /// The price in USD per completion token, NaN if the price is missing or invalid.
+    #[serde(default = "unknown_price", deserialize_with = "deserialize_price")]
+    pub completion: f64,
+
     pub request: Option<String>,
     pub image: Option<String>,
     pub web_search: Option<String>,
@@ -22,12 +31,104 @@ pub struct JsonPricing {
     pub input_cache_write: Option<String>,
 }
 
+impl JsonPricing {
+    /// Returns the price in USD per prompt token or an error if the price is unknown.
+    pub fn prompt_per_token(&self) -> Result<f64> {
+        known_price("prompt", self.prompt)
+    }
+
+    /// Returns the price in USD per completion token or an error if the price is unknown.
+    pub fn completion_per_token(&self) -> Result<f64> {
+        known_price("completion", self.completion)
+    }
+
+    /// Returns the estimated cost in USD for the given number of tokens.
+    /// The result is NaN if a price is unknown.
+    ///
+    /// # Arguments
+    /// * `prompt_tokens` - The number of prompt tokens.
+    /// * `completion_tokens` - The number of completion tokens.
+    pub fn estimated_cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        prompt_tokens as f64 * self.prompt + completion_tokens as f64 * self.completion
+    }
+}
+
+/// Returns the given price or an error if the price is unknown, i.e., NaN.
+///
+/// # Arguments
+/// * `name` - The name of the price used in the error message.
+/// * `price` - The price to check.
+fn known_price(name: &str, price: f64) -> Result<f64> {
+    if price.is_nan() {
+        return Err(Error::Deserialization(format!(
+            "The {} price is unknown",
+            name
+        )));
+    }
+
+    Ok(price)
+}
+
+/// Returns the value used for missing prices.
+fn unknown_price() -> f64 {
+    f64::NAN
+}
+
+/// Deserializes a price given as JSON string or number.
+/// Missing or invalid prices are deserialized as NaN.
+///
+/// # Arguments
+/// * `deserializer` - The deserializer to read the price from.
+fn deserialize_price<'de, D>(deserializer: D) -> std::result::Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum JsonPrice {
+        Number(f64),
+        Text(String),
+    }
+
+    let price = match Option::<JsonPrice>::deserialize(deserializer)? {
+        Some(JsonPrice::Number(price)) => price,
+        Some(JsonPrice::Text(price)) => parse_price(&price).unwrap_or(f64::NAN),
+        None => f64::NAN,
+    };
+
+    Ok(price)
+}
+
+/// Formats the given price per token as price per 1M tokens, e.g., "$0.10 / 1M tokens".
+///
+/// # Arguments
+/// * `price` - The price in USD per token.
+fn format_price_per_million(price: f64) -> String {
+    if price.is_nan() {
+        "unknown".to_string()
+    } else {
+        format!("${:.2} / 1M tokens", price * 1e6)
+    }
+}
+
+/// Parses the given price string as USD value.
+///
+/// # Arguments
+/// * `price` - The price string, e.g., "0.0000001".
+fn parse_price(price: &str) -> Result<f64> {
+    price
+        .trim()
+        .parse::<f64>()
+        .map_err(|e| Error::Deserialization(format!("Failed to parse price '{}': {}", price, e)))
+}
+
 impl std::fmt::Display for JsonPricing {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "Prompt: {}, Completion: {}",
-            self.prompt, self.completion,
+            format_price_per_million(self.prompt),
+            format_price_per_million(self.completion),
         )?;
 
         if let Some(request) = &self.request {
@@ -78,6 +179,8 @@ pub struct LLMModel {
     pub top_provider: JsonTopProvider,
     pub per_request_limits: Option<HashMap<String, String>>,
     pub supported_parameters: HashSet<String>,
+    #[serde(default)]
+    pub deprecation_date: Option<String>,
 }
 
 /// Represents the list of models available in the API.
@@ -85,6 +188,51 @@ pub struct LLMModel {
 pub struct JsonModels {
     #[serde(rename = "data")]
     pub models: Vec<LLMModel>,
+
+    /// True if the API paginates the models and further pages follow.
+    #[serde(default)]
+    pub has_more: bool,
+
+    /// The cursor of the next page, if provided by the API.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl JsonModels {
+    /// Returns the cursor for requesting the next page or None if this is the last page.
+    /// Falls back to the id of the last model if the API only reports `has_more`.
+    pub fn next_page_cursor(&self) -> Option<String> {
+        if let Some(cursor) = &self.next_cursor {
+            return Some(cursor.clone());
+        }
+
+        if self.has_more {
+            return self.models.last().map(|model| model.id.clone());
+        }
+
+        None
+    }
+}
+
+/// A single page of the models available in the API.
+#[derive(Debug)]
+pub struct ModelsPage {
+    pub models: Vec<LLMModel>,
+
+    /// The cursor for requesting the next page or None if this is the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl From<JsonModels> for ModelsPage {
+    fn from(models: JsonModels) -> Self {
+        let next_cursor = models.next_page_cursor();
+
+        Self {
+            models: models.models,
+            next_cursor,
+        }
+    }
 }
 
 /// The LLMModels to give information about the available models.
@@ -105,6 +253,375 @@ impl LLMModels {
     pub fn get_models(&self) -> &[LLMModel] {
         &self.models.models
     }
+
+    /// Returns the models with a context length of at least `min_tokens`.
+    ///
+    /// # Arguments
+    /// * `min_tokens` - The minimum context length in tokens.
+    pub fn with_min_context(&self, min_tokens: u64) -> impl Iterator<Item = &LLMModel> {
+        self.get_models().iter().with_min_context(min_tokens)
+    }
+
+    /// Returns the models supporting the given input modality, e.g., "image".
+    ///
+    /// # Arguments
+    /// * `modality` - The input modality the models must support.
+    pub fn with_input_modality<'a>(
+        &'a self,
+        modality: &'a str,
+    ) -> impl Iterator<Item = &'a LLMModel> {
+        self.get_models().iter().with_input_modality(modality)
+    }
+
+    /// Returns the models supporting images as input.
+    pub fn vision_models(&self) -> impl Iterator<Item = &LLMModel> {
+        self.get_models().iter().vision_models()
+    }
+
+    /// Returns the models sorted by the given key and order.
+    ///
+    /// # Arguments
+    /// * `key` - The key to sort the models by.
+    /// * `order` - The sort order.
+    pub fn sort_by(&self, key: ModelSortKey, order: SortOrder) -> Vec<&LLMModel> {
+        let mut models: Vec<&LLMModel> = self.get_models().iter().collect();
+        sort_models(&mut models, key, order);
+        models
+    }
+
+    /// Returns the cheapest model by prompt price meeting the given requirements or None
+    /// if no model meets them. Models with an unknown or variable, i.e., negative, prompt price
+    /// are never selected.
+    ///
+    /// # Arguments
+    /// * `requirements` - The requirements the model must meet.
+    pub fn cheapest_with_requirements(
+        &self,
+        requirements: &ModelRequirements,
+    ) -> Option<&LLMModel> {
+        self.get_models()
+            .iter()
+            .filter(|model| requirements.is_met_by(model))
+            .filter_map(|model| Some((model, model.pricing.prompt_per_token().ok()?)))
+            .filter(|(_, price)| *price >= 0.0)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(model, _)| model)
+    }
+
+    /// Returns the models sorted by the prompt price, cheapest first.
+    /// Models with an unparseable price are put at the end.
+    pub fn sorted_by_prompt_price(&self) -> Vec<&LLMModel> {
+        self.sort_by(ModelSortKey::PromptPrice, SortOrder::Ascending)
+    }
+
+    /// Returns the models sorted by the context length, largest first.
+    pub fn sorted_by_context_length_desc(&self) -> Vec<&LLMModel> {
+        self.sort_by(ModelSortKey::ContextLength, SortOrder::Descending)
+    }
+
+    /// Formats all models as a GitHub Markdown table.
+    ///
+    /// Supported column names are `"id"`, `"name"`, `"context"`, `"prompt_price"`,
+    /// `"completion_price"` and `"provider"`. Unknown column names are skipped.
+    ///
+    /// # Arguments
+    /// * `columns` - The columns to include in the table.
+    pub fn format_markdown_table(&self, columns: &[&str]) -> String {
+        format_markdown_table(self.get_models(), columns)
+    }
+
+    /// Formats the models matching the given filter as a GitHub Markdown table.
+    ///
+    /// # Arguments
+    /// * `columns` - The columns to include in the table.
+    /// * `filter` - Only models for which the filter returns true are included.
+    pub fn format_markdown_table_filtered<F>(&self, columns: &[&str], filter: F) -> String
+    where
+        F: Fn(&LLMModel) -> bool,
+    {
+        format_markdown_table(
+            self.get_models().iter().filter(|model| filter(model)),
+            columns,
+        )
+    }
+}
+
+/// Filters for iterators over models, allowing to compose multiple filters.
+pub trait LLMModelIterExt<'a>: Iterator<Item = &'a LLMModel> + Sized {
+    /// Filters the models to the ones with a context length of at least `min_tokens`.
+    ///
+    /// # Arguments
+    /// * `min_tokens` - The minimum context length in tokens.
+    fn with_min_context(self, min_tokens: u64) -> impl Iterator<Item = &'a LLMModel> {
+        self.filter(move |model| model.context_length >= min_tokens)
+    }
+
+    /// Filters the models to the ones supporting the given input modality, e.g., "image".
+    ///
+    /// # Arguments
+    /// * `modality` - The input modality the models must support.
+    fn with_input_modality(self, modality: &'a str) -> impl Iterator<Item = &'a LLMModel> {
+        self.filter(move |model| model.supports_input_modality(modality))
+    }
+
+    /// Filters the models to the ones supporting images as input.
+    fn vision_models(self) -> impl Iterator<Item = &'a LLMModel> {
+        self.filter(|model| model.is_vision_capable())
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a LLMModel>> LLMModelIterExt<'a> for I {}
+
+/// The requirements a model must meet, e.g., for selecting a model automatically.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelRequirements {
+    /// The minimum context length in tokens.
+    pub min_context: u64,
+
+    /// The maximum prompt price in USD per token.
+    pub max_prompt_price: Option<f64>,
+
+    /// The parameters the model must support, e.g., "tools".
+    pub supported_parameters: Vec<String>,
+}
+
+impl ModelRequirements {
+    /// Returns true if the given model meets the requirements.
+    /// A model with an unknown prompt price never meets a maximum prompt price.
+    ///
+    /// # Arguments
+    /// * `model` - The model to check.
+    pub fn is_met_by(&self, model: &LLMModel) -> bool {
+        if model.context_length < self.min_context {
+            return false;
+        }
+
+        if let Some(max_prompt_price) = self.max_prompt_price
+            && !model
+                .pricing
+                .prompt_per_token()
+                .is_ok_and(|price| price <= max_prompt_price)
+        {
+            return false;
+        }
+
+        self.supported_parameters
+            .iter()
+            .all(|parameter| model.supported_parameters.contains(parameter))
+    }
+}
+
+/// The keys the models can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelSortKey {
+    PromptPrice,
+    CompletionPrice,
+    ContextLength,
+    Name,
+    Created,
+}
+
+/// The order for sorting the models.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Sorts the given models by the given key and order.
+/// Models with an unparseable price are always put at the end.
+///
+/// # Arguments
+/// * `models` - The models to sort.
+/// * `key` - The key to sort the models by.
+/// * `order` - The sort order.
+pub fn sort_models(models: &mut [&LLMModel], key: ModelSortKey, order: SortOrder) {
+    let apply_order = |ordering: Ordering| match order {
+        SortOrder::Ascending => ordering,
+        SortOrder::Descending => ordering.reverse(),
+    };
+
+    let compare_prices = |a: Result<f64>, b: Result<f64>| match (a, b) {
+        (Ok(a), Ok(b)) => apply_order(a.total_cmp(&b)),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => Ordering::Equal,
+    };
+
+    models.sort_by(|a, b| match key {
+        ModelSortKey::PromptPrice => {
+            compare_prices(a.pricing.prompt_per_token(), b.pricing.prompt_per_token())
+        }
+        ModelSortKey::CompletionPrice => compare_prices(
+            a.pricing.completion_per_token(),
+            b.pricing.completion_per_token(),
+        ),
+        ModelSortKey::ContextLength => apply_order(a.context_length.cmp(&b.context_length)),
+        ModelSortKey::Name => apply_order(a.name.to_lowercase().cmp(&b.name.to_lowercase())),
+        ModelSortKey::Created => apply_order(a.created.cmp(&b.created)),
+    });
+}
+
+/// Formats the given models as a GitHub Markdown table.
+///
+/// Supported column names are `"id"`, `"name"`, `"context"`, `"prompt_price"`,
+/// `"completion_price"` and `"provider"`. Unknown column names are skipped.
+///
+/// # Arguments
+/// * `models` - The models to include in the table.
+/// * `columns` - The columns to include in the table.
+pub fn format_markdown_table<'a>(
+    models: impl IntoIterator<Item = &'a LLMModel>,
+    columns: &[&str],
+) -> String {
+    let columns: Vec<&str> = columns
+        .iter()
+        .copied()
+        .filter(|column| {
+            let supported = MARKDOWN_COLUMNS.contains(column);
+            if !supported {
+                log::warn!("Unsupported markdown table column: {}", column);
+            }
+
+            supported
+        })
+        .collect();
+
+    let rows: Vec<Vec<String>> = models
+        .into_iter()
+        .map(|model| {
+            columns
+                .iter()
+                .map(|column| model.markdown_cell(column))
+                .collect()
+        })
+        .collect();
+
+    // the width of each column is the maximum width of its header and cells
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(column.chars().count()))
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect();
+
+    let format_row = |cells: &[&str]| -> String {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!(" {:<width$} ", cell, width = width))
+            .collect();
+
+        format!("|{}|\n", cells.join("|"))
+    };
+
+    let mut table = format_row(&columns);
+
+    let separators: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    let separators: Vec<&str> = separators.iter().map(String::as_str).collect();
+    table.push_str(&format_row(&separators));
+
+    for row in rows.iter() {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        table.push_str(&format_row(&cells));
+    }
+
+    table
+}
+
+/// The columns supported by `format_markdown_table`.
+const MARKDOWN_COLUMNS: [&str; 6] = [
+    "id",
+    "name",
+    "context",
+    "prompt_price",
+    "completion_price",
+    "provider",
+];
+
+impl LLMModel {
+    /// Returns the estimated cost in USD for the given usage of the model.
+    /// The result is NaN if a price of the model is unknown.
+    ///
+    /// # Arguments
+    /// * `usage` - The token usage of a request.
+    pub fn estimated_cost(&self, usage: &Usage) -> f64 {
+        self.pricing.estimated_cost(
+            usage.prompt_tokens.max(0) as u64,
+            usage.completion_tokens.max(0) as u64,
+        )
+    }
+
+    /// Returns true if the deprecation date of the model has passed.
+    /// Models without or with an unparsable deprecation date are never deprecated.
+    pub fn is_deprecated(&self) -> bool {
+        self.parsed_deprecation_date()
+            .is_some_and(|date| date <= chrono::Utc::now())
+    }
+
+    /// Returns the number of days until the model is deprecated or None if no
+    /// deprecation date is known. The value is negative for already deprecated models.
+    pub fn days_until_deprecation(&self) -> Option<i64> {
+        self.parsed_deprecation_date()
+            .map(|date| (date - chrono::Utc::now()).num_days())
+    }
+
+    /// Parses the ISO-8601 deprecation date of the model.
+    fn parsed_deprecation_date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let date = self.deprecation_date.as_ref()?;
+        match chrono::DateTime::parse_from_rfc3339(date) {
+            Ok(date) => Some(date.with_timezone(&chrono::Utc)),
+            Err(err) => {
+                log::warn!(
+                    "Invalid deprecation date '{}' of model {}: {}",
+                    date,
+                    self.id,
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns true if the model supports the given input modality, e.g., "image".
+    ///
+    /// # Arguments
+    /// * `modality` - The input modality to check.
+    pub fn supports_input_modality(&self, modality: &str) -> bool {
+        self.architecture
+            .input_modalities
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(modality))
+    }
+
+    /// Returns true if the model supports images as input.
+    pub fn is_vision_capable(&self) -> bool {
+        self.supports_input_modality("image")
+    }
+
+    /// Returns the provider of the model, i.e., the prefix of the model id.
+    pub fn provider(&self) -> &str {
+        self.id.split('/').next().unwrap_or_default()
+    }
+
+    /// Returns the content of the given markdown table column for the model.
+    fn markdown_cell(&self, column: &str) -> String {
+        match column {
+            "id" => self.id.clone(),
+            "name" => self.name.replace('|', "\\|"),
+            "context" => self.context_length.to_string(),
+            "prompt_price" => format_price_per_million(self.pricing.prompt),
+            "completion_price" => format_price_per_million(self.pricing.completion),
+            "provider" => self.provider().to_string(),
+            _ => String::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -117,4 +634,243 @@ mod test {
         let data: JsonModels = serde_json::from_str(json_data).unwrap();
         assert!(!data.models.is_empty(), "Failed to deserialize models");
     }
+
+    #[test]
+    fn test_pricing_per_token() {
+        let json_data = include_str!("../test_data/models.json");
+        let data: JsonModels = serde_json::from_str(json_data).unwrap();
+
+        let pricing = &data.models[0].pricing;
+        assert_eq!(pricing.prompt_per_token().unwrap(), 0.0000015);
+        assert_eq!(pricing.completion_per_token().unwrap(), 0.000006);
+
+        assert!(matches!(
+            parse_price("free"),
+            Err(Error::Deserialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_pricing_deserialization() {
+        let pricing: JsonPricing =
+            serde_json::from_str(r#"{"prompt": "0.0000001", "completion": 0.0000004}"#).unwrap();
+        assert_eq!(pricing.prompt, 0.0000001);
+        assert_eq!(pricing.completion, 0.0000004);
+        assert_eq!(
+            pricing.to_string(),
+            "Prompt: $0.10 / 1M tokens, Completion: $0.40 / 1M tokens"
+        );
+
+        let pricing: JsonPricing =
+            serde_json::from_str(r#"{"prompt": "free", "completion": null}"#).unwrap();
+        assert!(pricing.prompt.is_nan());
+        assert!(pricing.completion.is_nan());
+        assert!(pricing.prompt_per_token().is_err());
+
+        let pricing: JsonPricing = serde_json::from_str("{}").unwrap();
+        assert!(pricing.completion_per_token().is_err());
+    }
+
+    #[test]
+    fn test_estimated_cost() {
+        let json_data = include_str!("../test_data/models.json");
+        let data: JsonModels = serde_json::from_str(json_data).unwrap();
+
+        // prompt: 0.0000015, completion: 0.000006
+        let model = &data.models[0];
+        let cost = model.pricing.estimated_cost(10_000, 2_000);
+        assert!((cost - 0.027).abs() < 1e-12);
+
+        let usage: Usage = serde_json::from_str(
+            r#"{"prompt_tokens": 1000, "completion_tokens": 500, "total_tokens": 1500}"#,
+        )
+        .unwrap();
+        assert!((model.estimated_cost(&usage) - 0.0045).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_deprecation_date() {
+        let json_data = include_str!("../test_data/models.json");
+        let data: JsonModels = serde_json::from_str(json_data).unwrap();
+
+        let mut model = data.models.into_iter().next().unwrap();
+        assert!(model.deprecation_date.is_none());
+        assert!(!model.is_deprecated());
+        assert_eq!(model.days_until_deprecation(), None);
+
+        model.deprecation_date = Some("2000-01-01T00:00:00Z".to_string());
+        assert!(model.is_deprecated());
+        assert!(model.days_until_deprecation().unwrap() < 0);
+
+        let in_ten_days =
+            chrono::Utc::now() + chrono::Duration::days(10) + chrono::Duration::hours(1);
+        model.deprecation_date = Some(in_ten_days.to_rfc3339());
+        assert!(!model.is_deprecated());
+        assert_eq!(model.days_until_deprecation(), Some(10));
+
+        model.deprecation_date = Some("next week".to_string());
+        assert!(!model.is_deprecated());
+        assert_eq!(model.days_until_deprecation(), None);
+    }
+
+    #[test]
+    fn test_cheapest_with_requirements() {
+        let json_data = include_str!("../test_data/models.json");
+        let models = LLMModels::new(serde_json::from_str(json_data).unwrap());
+
+        let requirements = ModelRequirements {
+            min_context: 128000,
+            max_prompt_price: None,
+            supported_parameters: vec!["tools".to_string()],
+        };
+        let cheapest = models.cheapest_with_requirements(&requirements).unwrap();
+        assert!(requirements.is_met_by(cheapest));
+
+        let cheapest_price = cheapest.pricing.prompt_per_token().unwrap();
+        assert!(cheapest_price >= 0.0);
+        assert!(
+            models
+                .get_models()
+                .iter()
+                .filter(|model| requirements.is_met_by(model))
+                .all(|model| model
+                    .pricing
+                    .prompt_per_token()
+                    .is_ok_and(|price| price < 0.0 || price >= cheapest_price))
+        );
+
+        let requirements = ModelRequirements {
+            min_context: u64::MAX,
+            ..Default::default()
+        };
+        assert!(models.cheapest_with_requirements(&requirements).is_none());
+    }
+
+    #[test]
+    fn test_next_page_cursor() {
+        let json_data = include_str!("../test_data/models.json");
+        let mut data: JsonModels = serde_json::from_str(json_data).unwrap();
+        assert!(!data.has_more);
+        assert_eq!(data.next_page_cursor(), None);
+
+        data.has_more = true;
+        let last_id = data.models.last().unwrap().id.clone();
+        assert_eq!(data.next_page_cursor(), Some(last_id));
+
+        data.next_cursor = Some("cursor".to_string());
+        let page = ModelsPage::from(data);
+        assert_eq!(page.next_cursor.as_deref(), Some("cursor"));
+    }
+
+    #[test]
+    fn test_with_min_context() {
+        let json_data = include_str!("../test_data/models.json");
+        let models = LLMModels::new(serde_json::from_str(json_data).unwrap());
+
+        let filtered: Vec<&LLMModel> = models.with_min_context(128000).collect();
+        assert!(!filtered.is_empty());
+        assert!(filtered.len() < models.get_models().len());
+        assert!(filtered.iter().all(|m| m.context_length >= 128000));
+
+        assert_eq!(
+            models.with_min_context(0).count(),
+            models.get_models().len()
+        );
+    }
+
+    #[test]
+    fn test_vision_models() {
+        let json_data = include_str!("../test_data/models.json");
+        let models = LLMModels::new(serde_json::from_str(json_data).unwrap());
+
+        let vision: Vec<&LLMModel> = models.vision_models().collect();
+        assert!(!vision.is_empty());
+        assert!(vision.len() < models.get_models().len());
+        assert!(vision.iter().all(|m| m.is_vision_capable()));
+        assert_eq!(models.with_input_modality("image").count(), vision.len());
+
+        // filters can be composed
+        let composed: Vec<&LLMModel> = models.with_min_context(200000).vision_models().collect();
+        assert!(
+            composed
+                .iter()
+                .all(|m| m.is_vision_capable() && m.context_length >= 200000)
+        );
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let json_data = include_str!("../test_data/models.json");
+        let mut data: JsonModels = serde_json::from_str(json_data).unwrap();
+        data.models[0].pricing.prompt = f64::NAN;
+        let models = LLMModels::new(data);
+
+        let sorted = models.sorted_by_prompt_price();
+        assert_eq!(sorted.len(), models.get_models().len());
+        assert!(sorted.last().unwrap().pricing.prompt.is_nan());
+        assert!(sorted[..sorted.len() - 1].windows(2).all(|w| {
+            w[0].pricing.prompt_per_token().unwrap() <= w[1].pricing.prompt_per_token().unwrap()
+        }));
+
+        // unparseable prices are put at the end for both orders
+        let sorted = models.sort_by(ModelSortKey::PromptPrice, SortOrder::Descending);
+        assert!(sorted.last().unwrap().pricing.prompt.is_nan());
+
+        let sorted = models.sorted_by_context_length_desc();
+        assert!(
+            sorted
+                .windows(2)
+                .all(|w| w[0].context_length >= w[1].context_length)
+        );
+
+        let sorted = models.sort_by(ModelSortKey::Name, SortOrder::Ascending);
+        assert!(
+            sorted
+                .windows(2)
+                .all(|w| w[0].name.to_lowercase() <= w[1].name.to_lowercase())
+        );
+    }
+
+    #[test]
+    fn test_format_markdown_table() {
+        let json_data = include_str!("../test_data/models.json");
+        let mut json: serde_json::Value = serde_json::from_str(json_data).unwrap();
+
+        // the width of a column is measured in characters, not in bytes
+        json["data"][0]["name"] = "Mistral: Ministral 3B (Präzision, 日本語)".into();
+        let models = LLMModels::new(serde_json::from_value(json).unwrap());
+
+        let table = models.format_markdown_table(&["id", "name", "context", "provider"]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), models.get_models().len() + 2);
+
+        let header: Vec<&str> = lines[0].split('|').map(str::trim).collect();
+        assert_eq!(header, vec!["", "id", "name", "context", "provider", ""]);
+
+        let separator: Vec<&str> = lines[1].split('|').map(str::trim).collect();
+        assert_eq!(separator.len(), 6);
+        assert!(
+            separator[1..5]
+                .iter()
+                .all(|cell| cell.len() >= 3 && cell.chars().all(|c| c == '-'))
+        );
+
+        // all rows must have the same width
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|line| line.chars().count() == width));
+        assert!(lines.iter().any(|line| line.contains("日本語")));
+    }
+
+    #[test]
+    fn test_format_markdown_table_filtered() {
+        let json_data = include_str!("../test_data/models.json");
+        let models = LLMModels::new(serde_json::from_str(json_data).unwrap());
+
+        let table =
+            models.format_markdown_table_filtered(&["id", "unknown"], |m| m.provider() == "openai");
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines[0].split('|').map(str::trim).nth(1), Some("id"));
+        assert!(lines[2..].iter().all(|line| line.contains("openai/")));
+    }
 }
@@ -0,0 +1,62 @@
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::{Instant, SystemTime};
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::{Instant, SystemTime};
+
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+compile_error!("Building for wasm32 requires the `wasm` feature of the `ai` crate");
+
+/// A marker for values that must be sent between threads on native targets.
+/// In the browser, the futures of the fetch API are bound to the JavaScript thread,
+/// so the marker is implemented for all types there.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> MaybeSend for T {}
+
+/// A marker for values that must be sent between threads on native targets.
+/// In the browser, the futures of the fetch API are bound to the JavaScript thread,
+/// so the marker is implemented for all types there.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> MaybeSend for T {}
+
+/// Waits for the given duration, using the timers of the browser on wasm32.
+///
+/// # Arguments
+/// * `duration` - The duration to wait.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(duration).await;
+
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Runs the given future and returns its output, or `None` if it did not complete within
+/// the given duration.
+///
+/// # Arguments
+/// * `duration` - The maximum duration of the future.
+/// * `future` - The future to run.
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return tokio::time::timeout(duration, future).await.ok();
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let future = std::pin::pin!(future);
+        let sleep = std::pin::pin!(sleep(duration));
+
+        match futures::future::select(future, sleep).await {
+            futures::future::Either::Left((output, _)) => Some(output),
+            futures::future::Either::Right(_) => None,
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use log::debug;
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use crate::Error;
+use crate::platform::SystemTime;
+
+/// The configuration for retrying failed requests with exponential backoff.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+
+    /// The delay before the first retry.
+    pub initial_delay: Duration,
+
+    /// The upper bound for the delay between two attempts.
+    pub max_delay: Duration,
+
+    /// The factor the delay is multiplied with after each attempt.
+    pub backoff_factor: f64,
+
+    /// If true, a random jitter is applied to the delay to prevent many clients
+    /// from retrying at the same time.
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            backoff_factor: 2.0,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Returns the delay to wait before the given retry.
+    ///
+    /// # Arguments
+    /// * `retry` - The number of the retry, starting with 1 for the first retry.
+    pub fn delay_for_retry(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.backoff_factor.powi(exponent);
+        let delay = delay.clamp(0.0, self.max_delay.as_secs_f64());
+
+        // use equal jitter, i.e., keep half of the delay and randomize the other half
+        let delay = if self.jitter {
+            delay / 2.0 + rand::rng().random_range(0.0..=delay / 2.0)
+        } else {
+            delay
+        };
+
+        Duration::from_secs_f64(delay)
+    }
+}
+
+impl RetryConfig {
+    /// Returns the delay to wait before retrying after the given error.
+    /// Uses the `Retry-After` value of a rate limit error, clamped to `max_delay`,
+    /// and falls back to the exponential backoff otherwise.
+    ///
+    /// # Arguments
+    /// * `retry` - The number of the retry, starting with 1 for the first retry.
+    /// * `error` - The error of the failed attempt.
+    pub fn delay_for_error(&self, retry: u32, error: &Error) -> Duration {
+        match error {
+            Error::RateLimit {
+                retry_after: Some(retry_after),
+            } => {
+                debug!("Retry-After: {:?}", retry_after);
+                (*retry_after).min(self.max_delay)
+            }
+            _ => self.delay_for_retry(retry),
+        }
+    }
+}
+
+/// Parses the `Retry-After` header, which is either a number of seconds or an HTTP-date.
+/// Returns None if the header is missing or invalid.
+///
+/// # Arguments
+/// * `headers` - The headers of the response.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+
+    // the dates are compared as durations since the epoch, as the current time is not
+    // taken from the standard library in the browser
+    let date = date.duration_since(std::time::UNIX_EPOCH).ok()?;
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()?;
+
+    // a date in the past means that the request can be retried immediately
+    Some(date.saturating_sub(now))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+        headers.insert(RETRY_AFTER, date.parse().unwrap());
+        let retry_after = parse_retry_after(&headers).unwrap();
+        assert!(retry_after > Duration::from_secs(55) && retry_after <= Duration::from_secs(60));
+
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_delay_for_error() {
+        let config = RetryConfig {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            backoff_factor: 2.0,
+            jitter: false,
+        };
+
+        let error = Error::RateLimit {
+            retry_after: Some(Duration::from_secs(3)),
+        };
+        assert_eq!(config.delay_for_error(1, &error), Duration::from_secs(3));
+
+        let error = Error::RateLimit {
+            retry_after: Some(Duration::from_secs(60)),
+        };
+        assert_eq!(config.delay_for_error(1, &error), Duration::from_secs(10));
+
+        let error = Error::RateLimit { retry_after: None };
+        assert_eq!(
+            config.delay_for_error(2, &error),
+            Duration::from_millis(200)
+        );
+
+        let error = Error::HTTPErrorWithStatusCode(reqwest::StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            config.delay_for_error(1, &error),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn test_delay_for_retry() {
+        let config = RetryConfig {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            backoff_factor: 2.0,
+            jitter: false,
+        };
+
+        assert_eq!(config.delay_for_retry(1), Duration::from_millis(100));
+        assert_eq!(config.delay_for_retry(2), Duration::from_millis(200));
+        assert_eq!(config.delay_for_retry(3), Duration::from_millis(350));
+        assert_eq!(config.delay_for_retry(4), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_delay_for_retry_with_jitter() {
+        let config = RetryConfig {
+            initial_delay: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        for _ in 0..100 {
+            let delay = config.delay_for_retry(2);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(200));
+        }
+    }
+}
@@ -0,0 +1,837 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::json_types::{JsonFunctionCall, null_as_default};
+use crate::platform::MaybeSend;
+use crate::{
+    Error, FinishReason, JsonToolCall, Message, MessageContent, NativeFinishReason, Result,
+    RetryConfig, Role, Usage,
+};
+
+/// A single chunk of a streamed chat completion response.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StreamChunk {
+    pub id: String,
+
+    #[serde(default)]
+    pub provider: String,
+
+    #[serde(default)]
+    pub model: String,
+
+    #[serde(default)]
+    pub object: String,
+
+    #[serde(default)]
+    pub created: i64,
+
+    #[serde(default)]
+    pub choices: Vec<StreamChoice>,
+
+    /// The usage of the request, only sent with the last chunk.
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// The partial choice of a streamed chat completion response.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StreamChoice {
+    pub index: i64,
+    pub delta: MessageDelta,
+
+    /// The finish reason, only sent with the last chunk of the choice.
+    #[serde(default)]
+    pub finish_reason: Option<FinishReason>,
+
+    #[serde(default)]
+    pub native_finish_reason: Option<NativeFinishReason>,
+}
+
+/// The part of a message sent in a single chunk.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MessageDelta {
+    #[serde(default)]
+    pub role: Option<Role>,
+
+    #[serde(default)]
+    pub content: Option<String>,
+
+    #[serde(default)]
+    pub reasoning: Option<String>,
+
+    #[serde(default)]
+    pub refusal: Option<String>,
+
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tool_calls: Vec<ToolCallDelta>,
+}
+
+/// The part of a tool call sent in a single chunk.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ToolCallDelta {
+    /// The index of the tool call the part belongs to.
+    pub index: i64,
+
+    #[serde(default)]
+    pub id: Option<String>,
+
+    #[serde(default)]
+    pub r#type: Option<String>,
+
+    #[serde(default)]
+    pub function: Option<FunctionCallDelta>,
+}
+
+/// The part of a function call sent in a single chunk.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunctionCallDelta {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// A fragment of the JSON arguments.
+    #[serde(default)]
+    pub arguments: Option<String>,
+}
+
+/// Assembles the tool call deltas of a streamed choice into the complete tool calls.
+#[derive(Debug, Default)]
+pub struct ToolCallDeltaAccumulator {
+    tool_calls: BTreeMap<i64, JsonToolCall>,
+}
+
+impl ToolCallDeltaAccumulator {
+    /// Creates a new empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges the given delta into the tool call with the same index.
+    /// The fragments of the name and the arguments are appended in order.
+    ///
+    /// # Arguments
+    /// * `delta` - The delta to merge.
+    pub fn push(&mut self, delta: &ToolCallDelta) {
+        let entry = self
+            .tool_calls
+            .entry(delta.index)
+            .or_insert_with(|| JsonToolCall {
+                index: delta.index,
+                id: String::new(),
+                r#type: "function".to_string(),
+                function_call: JsonFunctionCall {
+                    name: String::new(),
+                    arguments: String::new(),
+                },
+            });
+
+        if let Some(id) = delta.id.as_ref().filter(|id| !id.is_empty()) {
+            entry.id = id.clone();
+        }
+
+        if let Some(r#type) = &delta.r#type {
+            entry.r#type = r#type.clone();
+        }
+
+        if let Some(function) = &delta.function {
+            if let Some(name) = &function.name {
+                entry.function_call.name.push_str(name);
+            }
+
+            if let Some(arguments) = &function.arguments {
+                entry.function_call.arguments.push_str(arguments);
+            }
+        }
+    }
+
+    /// Returns the assembled tool calls ordered by their index.
+    /// Tool calls without arguments, e.g., of tools without parameters, get the empty
+    /// object `{}` as arguments.
+    /// Fails if the arguments of a tool call are not valid JSON, e.g., because the stream
+    /// ended early.
+    pub fn complete(&self) -> Result<Vec<JsonToolCall>> {
+        let mut tool_calls: Vec<JsonToolCall> = self.tool_calls.values().cloned().collect();
+
+        for tool_call in tool_calls.iter_mut() {
+            if tool_call.function_call.arguments.trim().is_empty() {
+                tool_call.function_call.arguments = "{}".to_string();
+            }
+
+            serde_json::from_str::<serde_json::Value>(&tool_call.function_call.arguments).map_err(
+                |e| {
+                    Error::Deserialization(format!(
+                        "Invalid arguments for tool call '{}': {}",
+                        tool_call.function_call.name, e
+                    ))
+                },
+            )?;
+        }
+
+        Ok(tool_calls)
+    }
+}
+
+/// Assembles the message deltas of a streamed choice into the complete message.
+#[derive(Debug, Default)]
+pub struct DeltaAccumulator {
+    role: Option<Role>,
+    content: String,
+    reasoning: Option<String>,
+    refusal: Option<String>,
+    tool_calls: ToolCallDeltaAccumulator,
+}
+
+impl DeltaAccumulator {
+    /// Creates a new empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges the given delta into the message.
+    /// The content is concatenated and the tool calls are merged by their index.
+    ///
+    /// # Arguments
+    /// * `delta` - The delta to merge.
+    pub fn push(&mut self, delta: &MessageDelta) -> Result<()> {
+        if let Some(role) = &delta.role {
+            match &self.role {
+                Some(current) if current != role => {
+                    return Err(Error::InternalError(format!(
+                        "The role of the message changed from {:?} to {:?}",
+                        current, role
+                    )));
+                }
+                _ => self.role = Some(role.clone()),
+            }
+        }
+
+        if let Some(content) = &delta.content {
+            self.content.push_str(content);
+        }
+
+        if let Some(reasoning) = &delta.reasoning {
+            self.reasoning
+                .get_or_insert_with(String::new)
+                .push_str(reasoning);
+        }
+
+        if let Some(refusal) = &delta.refusal {
+            self.refusal
+                .get_or_insert_with(String::new)
+                .push_str(refusal);
+        }
+
+        for tool_call in delta.tool_calls.iter() {
+            self.tool_calls.push(tool_call);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the assembled message.
+    /// Fails if the arguments of a tool call are not valid JSON, e.g., because the stream
+    /// ended early.
+    pub fn finish(self) -> Result<Message> {
+        let tool_calls = self.tool_calls.complete()?;
+
+        Ok(Message {
+            role: self.role.unwrap_or(Role::Assistant),
+            content: MessageContent::Text(self.content),
+            tool_call_id: String::new(),
+            tool_calls,
+            refusal: self.refusal,
+            reasoning: self.reasoning,
+        })
+    }
+}
+
+/// The byte stream of the response body.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type ByteStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>>> + Send>>;
+
+/// The byte stream of the response body, which is not `Send` in the browser.
+#[cfg(target_arch = "wasm32")]
+pub(crate) type ByteStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>>>>>;
+
+/// The future opening a new byte stream when reconnecting.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type ReconnectFuture = Pin<Box<dyn Future<Output = Result<ByteStream>> + Send>>;
+
+/// The future opening a new byte stream when reconnecting, which is not `Send` in the browser.
+#[cfg(target_arch = "wasm32")]
+pub(crate) type ReconnectFuture = Pin<Box<dyn Future<Output = Result<ByteStream>>>>;
+
+/// Reopens the byte stream of the response after the event with the given id.
+pub(crate) type Reconnect = Box<dyn Fn(String) -> ReconnectFuture + Send + Sync>;
+
+/// The state for reconnecting an interrupted stream.
+struct Reconnection {
+    reconnect: Reconnect,
+    retry_config: RetryConfig,
+
+    /// The number of attempts so far, including the initial request.
+    attempts: u32,
+
+    /// The reconnect in progress, if any.
+    pending: Option<ReconnectFuture>,
+}
+
+/// The stream of chunks of a streamed chat completion response, parsed from the
+/// server-sent events of the response body.
+pub struct ChatCompletionStream {
+    bytes: ByteStream,
+    buffer: Vec<u8>,
+    /// The position in the buffer up to which no event separator was found.
+    scan_offset: usize,
+    pending: VecDeque<Result<StreamChunk>>,
+    done: bool,
+    last_event_id: Option<String>,
+    reconnection: Option<Reconnection>,
+}
+
+impl ChatCompletionStream {
+    /// Creates a new stream parsing the server-sent events from the given byte stream.
+    ///
+    /// # Arguments
+    /// * `bytes` - The byte stream of the response body.
+    pub fn from_byte_stream<S, B, E>(bytes: S) -> Self
+    where
+        S: Stream<Item = std::result::Result<B, E>> + MaybeSend + 'static,
+        B: AsRef<[u8]>,
+        E: Into<Error>,
+    {
+        let bytes = bytes.map(|item| item.map(|b| b.as_ref().to_vec()).map_err(Into::into));
+
+        Self {
+            bytes: Box::pin(bytes),
+            buffer: Vec::new(),
+            scan_offset: 0,
+            pending: VecDeque::new(),
+            done: false,
+            last_event_id: None,
+            reconnection: None,
+        }
+    }
+
+    /// Reconnects the stream if it is interrupted before the end, sending the id of the last
+    /// received event, and returns the updated stream.
+    /// Reconnecting requires event ids, as the server can only resume the stream after a known
+    /// event; sending the request again without one would start a new completion. Hence, an
+    /// interrupted stream without event ids fails with `Error::StreamInterrupted` right away,
+    /// as does a stream whose attempts of the retry configuration are used up.
+    /// A stream ending without `[DONE]` and without event ids ends normally.
+    ///
+    /// # Arguments
+    /// * `retry_config` - The configuration for the attempts and the backoff.
+    /// * `reconnect` - Reopens the byte stream for the given last event id.
+    pub(crate) fn with_reconnect(
+        mut self,
+        retry_config: RetryConfig,
+        reconnect: Reconnect,
+    ) -> Self {
+        self.reconnection = Some(Reconnection {
+            reconnect,
+            retry_config,
+            attempts: 1,
+            pending: None,
+        });
+        self
+    }
+
+    /// Returns the id of the last received event, if the server sent event ids.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Handles the interruption of the byte stream before the end, i.e., starts a reconnect
+    /// if configured, an event id was received and attempts are left.
+    /// Returns the error to yield if the stream cannot be continued.
+    ///
+    /// # Arguments
+    /// * `error` - The error interrupting the stream, if any.
+    fn interrupt(&mut self, error: Option<Error>) -> Option<Error> {
+        let last_event_id = self.last_event_id.clone();
+        let Some(reconnection) = &mut self.reconnection else {
+            self.done = true;
+            return error;
+        };
+
+        let Some(event_id) = last_event_id.clone() else {
+            log::error!(
+                "Stream interrupted without event ids, cannot resume{}",
+                error.map(|e| format!(": {}", e)).unwrap_or_default()
+            );
+            self.done = true;
+            return Some(Error::StreamInterrupted { last_event_id });
+        };
+
+        if reconnection.attempts >= reconnection.retry_config.max_attempts {
+            log::error!(
+                "Stream interrupted, giving up after {} attempts",
+                reconnection.attempts
+            );
+            self.done = true;
+            return Some(Error::StreamInterrupted { last_event_id });
+        }
+
+        let delay = reconnection
+            .retry_config
+            .delay_for_retry(reconnection.attempts);
+        reconnection.attempts += 1;
+        log::warn!(
+            "Stream interrupted{}. Reconnecting in {:?}...",
+            error.map(|e| format!(": {}", e)).unwrap_or_default(),
+            delay
+        );
+
+        // a partially received event is sent again after reconnecting
+        self.buffer.clear();
+        self.scan_offset = 0;
+
+        let reconnect = (reconnection.reconnect)(event_id);
+        reconnection.pending = Some(Box::pin(async move {
+            crate::platform::sleep(delay).await;
+            reconnect.await
+        }));
+
+        None
+    }
+
+    /// Appends the given bytes to the buffer and parses all complete events.
+    ///
+    /// # Arguments
+    /// * `bytes` - The received bytes.
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        // the line endings are normalized by dropping carriage returns
+        self.buffer
+            .extend(bytes.iter().filter(|b| **b != b'\r').copied());
+
+        // only the new bytes are searched, the complete events are removed at once afterwards
+        let mut consumed = 0;
+        while let Some(pos) = self.buffer[self.scan_offset..]
+            .windows(2)
+            .position(|w| w == b"\n\n")
+        {
+            let end = self.scan_offset + pos + 2;
+            let event = String::from_utf8_lossy(&self.buffer[consumed..end]).into_owned();
+            consumed = end;
+            self.scan_offset = end;
+            self.push_event(&event);
+
+            if self.done {
+                self.buffer.clear();
+                self.scan_offset = 0;
+                return;
+            }
+        }
+
+        self.buffer.drain(..consumed);
+
+        // the last byte may be the first half of a separator completed by the next bytes
+        self.scan_offset = self.buffer.len().saturating_sub(1);
+    }
+
+    /// Parses the given event and queues the resulting chunk or error.
+    ///
+    /// # Arguments
+    /// * `event` - The text of a single server-sent event.
+    fn push_event(&mut self, event: &str) {
+        let mut event_type = "message";
+        let mut data: Vec<&str> = Vec::new();
+
+        for line in event.lines() {
+            // lines starting with a colon are comments, e.g., keep-alive messages
+            if line.is_empty() || line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+
+            match field {
+                "event" => event_type = value,
+                "data" => data.push(value),
+                "id" => self.last_event_id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        // multiple data lines of a single event are joined by newlines
+        let data = data.join("\n");
+
+        match event_type {
+            "ping" => {}
+            "error" => {
+                log::error!("Stream error: {}", data);
+                self.pending.push_back(Err(Error::StreamError(data)));
+                self.done = true;
+            }
+            _ if data == "[DONE]" => self.done = true,
+            _ if data.is_empty() => {}
+            _ => self.pending.push_back(parse_chunk(&data)),
+        }
+    }
+}
+
+impl Stream for ChatCompletionStream {
+    type Item = Result<StreamChunk>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Poll::Ready(Some(item));
+            }
+
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            if let Some(reconnect) = self
+                .reconnection
+                .as_mut()
+                .and_then(|reconnection| reconnection.pending.as_mut())
+            {
+                let result = match reconnect.as_mut().poll(cx) {
+                    Poll::Ready(result) => result,
+                    Poll::Pending => return Poll::Pending,
+                };
+
+                if let Some(reconnection) = self.reconnection.as_mut() {
+                    reconnection.pending = None;
+                }
+
+                match result {
+                    Ok(bytes) => self.bytes = bytes,
+                    Err(err) if err.is_permanent() => {
+                        self.done = true;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    Err(err) => {
+                        if let Some(err) = self.interrupt(Some(err)) {
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                    }
+                }
+
+                continue;
+            }
+
+            match self.bytes.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) => self.push_bytes(&bytes),
+                Poll::Ready(Some(Err(err))) => {
+                    if let Some(err) = self.interrupt(Some(err)) {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+                Poll::Ready(None)
+                    if self.reconnection.is_some() && self.last_event_id.is_some() =>
+                {
+                    // a resumable stream ending before [DONE] was interrupted; an incomplete
+                    // trailing event is sent again after the last complete event
+                    if let Some(err) = self.interrupt(None) {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+                Poll::Ready(None) => {
+                    // parse a trailing event not terminated by an empty line
+                    let rest = std::mem::take(&mut self.buffer);
+                    self.scan_offset = 0;
+                    self.push_event(&String::from_utf8_lossy(&rest));
+                    self.done = true;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Parses the data of an event into a chunk.
+/// Errors reported by the provider within the data are returned as stream errors.
+///
+/// # Arguments
+/// * `data` - The data of the event.
+fn parse_chunk(data: &str) -> Result<StreamChunk> {
+    let value: serde_json::Value = serde_json::from_str(data).map_err(|e| {
+        log::error!("Failed to parse stream chunk: {}", e);
+        Error::Deserialization(e.to_string())
+    })?;
+
+    if let Some(error) = value.get("error") {
+        log::error!("Stream error: {}", error);
+        return Err(Error::StreamError(error.to_string()));
+    }
+
+    serde_json::from_value(value).map_err(|e| {
+        log::error!("Failed to parse stream chunk: {}", e);
+        Error::Deserialization(e.to_string())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Parses the deltas of the given chunks.
+    fn parse_deltas(chunks: &[&str]) -> Vec<MessageDelta> {
+        chunks
+            .iter()
+            .map(|chunk| {
+                let mut chunk: StreamChunk = serde_json::from_str(chunk).unwrap();
+                chunk.choices.remove(0).delta
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_split_events() {
+        // the events are split at arbitrary positions and use CRLF line endings
+        let body = "data: {\"id\": \"1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \"Hi\"}}]}\r\n\r\n\
+            data: {\"id\": \"1\",\r\ndata: \"choices\": []}\r\n\r\n\
+            data: [DONE]\r\n\r\n";
+        let parts: Vec<std::result::Result<Vec<u8>, Error>> = body
+            .as_bytes()
+            .chunks(7)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect();
+
+        let stream = ChatCompletionStream::from_byte_stream(futures::stream::iter(parts));
+        let chunks: Vec<Result<StreamChunk>> = stream.collect().await;
+
+        assert_eq!(chunks.len(), 2);
+        let first = chunks[0].as_ref().unwrap();
+        assert_eq!(first.choices[0].delta.content.as_deref(), Some("Hi"));
+
+        // the second event consists of two data lines
+        let second = chunks[1].as_ref().unwrap();
+        assert!(second.choices.is_empty());
+    }
+
+    #[test]
+    fn test_push_bytes_incremental_scan() {
+        let content = "x".repeat(1000);
+        let body = format!(
+            "data: {{\"id\": \"1\", \"choices\": [{{\"index\": 0, \"delta\": {{\"content\": \"{}\"}}}}]}}\n\n",
+            content
+        );
+
+        let mut stream = ChatCompletionStream::from_byte_stream(futures::stream::empty::<
+            std::result::Result<Vec<u8>, Error>,
+        >());
+
+        // the event is received byte by byte, the already scanned bytes are skipped
+        let (last, bytes) = body.as_bytes().split_last().unwrap();
+        for byte in bytes {
+            stream.push_bytes(&[*byte]);
+            assert_eq!(stream.scan_offset, stream.buffer.len() - 1);
+        }
+        assert!(stream.pending.is_empty());
+
+        stream.push_bytes(&[*last]);
+        assert!(stream.buffer.is_empty());
+        assert_eq!(stream.scan_offset, 0);
+
+        let chunk = stream.pending.pop_front().unwrap().unwrap();
+        assert_eq!(
+            chunk.choices[0].delta.content.as_deref(),
+            Some(content.as_str())
+        );
+    }
+
+    /// Creates a stream reading the given body from a cursor in small pieces.
+    fn stream_from_cursor(body: &'static [u8]) -> ChatCompletionStream {
+        use std::io::Read;
+
+        let mut cursor = std::io::Cursor::new(body);
+        let mut parts: Vec<std::result::Result<Vec<u8>, Error>> = Vec::new();
+        loop {
+            let mut part = [0u8; 16];
+            let len = cursor.read(&mut part).unwrap();
+            if len == 0 {
+                break;
+            }
+
+            parts.push(Ok(part[..len].to_vec()));
+        }
+
+        ChatCompletionStream::from_byte_stream(futures::stream::iter(parts))
+    }
+
+    #[tokio::test]
+    async fn test_stream_event_types() {
+        // data events are yielded, pings are skipped and [DONE] ends the stream
+        let stream = stream_from_cursor(
+            b"data: {\"id\": \"1\", \"choices\": []}\n\n\
+              event: ping\ndata: {}\n\n\
+              data: {\"id\": \"2\", \"choices\": []}\n\n\
+              data: [DONE]\n\n\
+              data: {\"id\": \"3\", \"choices\": []}\n\n",
+        );
+        let ids: Vec<String> = stream.map(|chunk| chunk.unwrap().id).collect().await;
+        assert_eq!(ids, ["1", "2"]);
+
+        // error events are yielded as errors and end the stream
+        let stream = stream_from_cursor(
+            b"data: {\"id\": \"1\", \"choices\": []}\n\n\
+              event: error\ndata: {\"message\": \"overloaded\"}\n\n\
+              data: {\"id\": \"2\", \"choices\": []}\n\n",
+        );
+        let chunks: Vec<Result<StreamChunk>> = stream.collect().await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ref().unwrap().id, "1");
+        assert!(matches!(
+            &chunks[1],
+            Err(Error::StreamError(body)) if body == "{\"message\": \"overloaded\"}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stream_interrupted_without_event_ids() {
+        let parts: Vec<std::result::Result<Vec<u8>, Error>> = vec![
+            Ok(b"data: {\"id\": \"1\", \"choices\": []}\n\n".to_vec()),
+            Err(Error::StreamError("connection reset".to_string())),
+        ];
+
+        // without an event id the request must never be sent again
+        let stream = ChatCompletionStream::from_byte_stream(futures::stream::iter(parts))
+            .with_reconnect(
+                RetryConfig::default(),
+                Box::new(|_| panic!("The stream must not be reconnected")),
+            );
+
+        let chunks: Vec<Result<StreamChunk>> = stream.collect().await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ref().unwrap().id, "1");
+        assert!(matches!(
+            chunks[1],
+            Err(Error::StreamInterrupted {
+                last_event_id: None
+            })
+        ));
+    }
+
+    #[test]
+    fn test_accumulate_content() {
+        let deltas = parse_deltas(&[
+            r#"{"id": "1", "choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}}]}"#,
+            r#"{"id": "1", "choices": [{"index": 0, "delta": {"content": "lo!"}}]}"#,
+            r#"{"id": "1", "choices": [{"index": 0, "delta": {"content": null}, "finish_reason": "stop"}]}"#,
+        ]);
+
+        let mut accumulator = DeltaAccumulator::new();
+        for delta in deltas.iter() {
+            accumulator.push(delta).unwrap();
+        }
+
+        let message = accumulator.finish().unwrap();
+        assert_eq!(message.role, Role::Assistant);
+        assert_eq!(message.content.to_text(), "Hello!");
+        assert!(message.tool_calls.is_empty());
+    }
+
+    #[test]
+    fn test_accumulate_tool_calls() {
+        let deltas = parse_deltas(&[
+            r#"{"id": "1", "choices": [{"index": 0, "delta": {"role": "assistant", "tool_calls": [
+                {"index": 0, "id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": ""}}
+            ]}}]}"#,
+            r#"{"id": "1", "choices": [{"index": 0, "delta": {"tool_calls": [
+                {"index": 0, "function": {"arguments": "{\"loc"}},
+                {"index": 1, "id": "call_2", "type": "function", "function": {"name": "get_time", "arguments": "{}"}}
+            ]}}]}"#,
+            r#"{"id": "1", "choices": [{"index": 0, "delta": {"tool_calls": [
+                {"index": 0, "function": {"arguments": "ation\":\"Paris\"}"}}
+            ]}}]}"#,
+        ]);
+
+        let mut accumulator = DeltaAccumulator::new();
+        for delta in deltas.iter() {
+            accumulator.push(delta).unwrap();
+        }
+
+        let message = accumulator.finish().unwrap();
+        assert_eq!(message.tool_calls.len(), 2);
+        assert_eq!(message.tool_calls[0].id, "call_1");
+        assert_eq!(message.tool_calls[0].function_call.name, "get_weather");
+        assert_eq!(
+            message.tool_calls[0].function_call.arguments,
+            r#"{"location":"Paris"}"#
+        );
+        assert_eq!(message.tool_calls[1].function_call.name, "get_time");
+    }
+
+    #[test]
+    fn test_tool_call_delta_accumulator() {
+        let deltas: Vec<ToolCallDelta> = serde_json::from_str(
+            r#"[
+                {"index": 1, "id": "call_2", "type": "function", "function": {"name": "get_time", "arguments": ""}},
+                {"index": 0, "id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"loc"}},
+                {"index": 0, "function": {"arguments": "ation\""}},
+                {"index": 1, "function": {"arguments": "{}"}}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut accumulator = ToolCallDeltaAccumulator::new();
+        for delta in deltas.iter() {
+            accumulator.push(delta);
+        }
+
+        // the arguments of the first tool call are still incomplete
+        assert!(matches!(
+            accumulator.complete(),
+            Err(Error::Deserialization(_))
+        ));
+
+        let delta: ToolCallDelta =
+            serde_json::from_str(r#"{"index": 0, "function": {"arguments": ":\"Paris\"}"}}"#)
+                .unwrap();
+        accumulator.push(&delta);
+
+        let tool_calls = accumulator.complete().unwrap();
+        assert_eq!(tool_calls.len(), 2);
+        assert_eq!(tool_calls[0].id, "call_1");
+        assert_eq!(
+            tool_calls[0].function_call.arguments,
+            r#"{"location":"Paris"}"#
+        );
+        assert_eq!(tool_calls[1].id, "call_2");
+        assert_eq!(tool_calls[1].function_call.arguments, "{}");
+    }
+
+    #[test]
+    fn test_tool_call_delta_accumulator_without_arguments() {
+        let delta: ToolCallDelta = serde_json::from_str(
+            r#"{"index": 0, "id": "call_1", "type": "function", "function": {"name": "get_time", "arguments": ""}}"#,
+        )
+        .unwrap();
+
+        let mut accumulator = ToolCallDeltaAccumulator::new();
+        accumulator.push(&delta);
+
+        let tool_calls = accumulator.complete().unwrap();
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0].function_call.name, "get_time");
+        assert_eq!(tool_calls[0].function_call.arguments, "{}");
+    }
+
+    #[test]
+    fn test_accumulate_incomplete_arguments() {
+        let deltas = parse_deltas(&[
+            r#"{"id": "1", "choices": [{"index": 0, "delta": {"tool_calls": [
+                {"index": 0, "id": "call_1", "function": {"name": "get_weather", "arguments": "{\"loc"}}
+            ]}}]}"#,
+        ]);
+
+        let mut accumulator = DeltaAccumulator::new();
+        accumulator.push(&deltas[0]).unwrap();
+
+        assert!(matches!(
+            accumulator.finish(),
+            Err(Error::Deserialization(_))
+        ));
+    }
+}
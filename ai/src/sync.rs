@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use reqwest::Url;
+
+use crate::{ChatCompletionParameter, Choice, Client, Error, LLMModels, Result};
+
+/// A blocking client for code without an async context, e.g., synchronous CLI tools or
+/// FFI bindings. It wraps a `Client` and runs its requests on a dedicated single-threaded
+/// runtime.
+///
+/// The methods must not be called from within an async context, as blocking on the runtime
+/// there panics.
+pub struct SyncClient {
+    client: Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl SyncClient {
+    /// Creates a new `SyncClient` instance with the given API key and URL.
+    ///
+    /// # Arguments
+    /// * `api_key` - The API key to authenticate requests.
+    /// * `api_url` - The base URL for the API.
+    pub fn new(api_key: String, api_url: Url) -> Result<Self> {
+        Self::from_client(Client::new(api_key, api_url)?)
+    }
+
+    /// Creates a new `SyncClient` wrapping the given client, e.g., configured via its builders.
+    ///
+    /// # Arguments
+    /// * `client` - The client to wrap.
+    pub fn from_client(client: Client) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| {
+                log::error!("Failed to create runtime: {}", e);
+                Error::IO(Box::new(e))
+            })?;
+
+        Ok(Self { client, runtime })
+    }
+
+    /// Returns the wrapped async client.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Sends a chat completion request to the API and blocks until the response is received.
+    ///
+    /// # Arguments
+    /// * `parameter` - The parameter for the chat completion request.
+    pub fn chat_completion(&self, parameter: &ChatCompletionParameter<'_>) -> Result<Vec<Choice>> {
+        self.runtime
+            .block_on(self.client.chat_completion(parameter))
+    }
+
+    /// Returns the models and blocks until they are fetched, if they are not cached yet.
+    pub fn get_models(&self) -> Result<Arc<LLMModels>> {
+        self.runtime.block_on(self.client.get_models())
+    }
+}
@@ -0,0 +1,133 @@
+use std::future::Future;
+
+use crate::{ChatCompletionResponse, Result};
+
+#[cfg(feature = "otel")]
+use opentelemetry::{
+    Context, KeyValue, global,
+    trace::{FutureExt as _, Status, TraceContextExt as _, Tracer as _},
+};
+
+/// The name of the span of a chat completion request.
+#[cfg(feature = "otel")]
+const CHAT_COMPLETION_SPAN: &str = "ai.chat_completion";
+
+/// The OpenTelemetry span of a chat completion request, including all of its retries.
+/// Without the `otel` feature, the span is a no-op.
+pub(crate) struct ChatCompletionSpan {
+    #[cfg(feature = "otel")]
+    context: Context,
+}
+
+impl ChatCompletionSpan {
+    /// Starts the span as child of the current context.
+    ///
+    /// # Arguments
+    /// * `model` - The model of the request.
+    #[cfg(feature = "otel")]
+    pub(crate) fn start(model: &str) -> Self {
+        let tracer = global::tracer("ai");
+        let mut span = tracer.start_with_context(CHAT_COMPLETION_SPAN, &Context::current());
+        opentelemetry::trace::Span::set_attribute(
+            &mut span,
+            KeyValue::new("ai.model", model.to_string()),
+        );
+
+        Self {
+            context: Context::current_with_span(span),
+        }
+    }
+
+    /// Starts the span as child of the current context.
+    ///
+    /// # Arguments
+    /// * `model` - The model of the request.
+    #[cfg(not(feature = "otel"))]
+    #[inline(always)]
+    pub(crate) fn start(_model: &str) -> Self {
+        Self {}
+    }
+
+    /// Runs the given future with the span as current context, so that the trace context
+    /// is propagated to the HTTP requests.
+    ///
+    /// # Arguments
+    /// * `future` - The future sending the request.
+    pub(crate) async fn in_scope<F: Future>(&self, future: F) -> F::Output {
+        #[cfg(feature = "otel")]
+        let future = future.with_context(self.context.clone());
+
+        future.await
+    }
+
+    /// Records the usage and the finish reason of the response and ends the span.
+    ///
+    /// # Arguments
+    /// * `response` - The result of the request.
+    #[cfg(feature = "otel")]
+    pub(crate) fn end(self, response: &Result<ChatCompletionResponse>) {
+        let span = self.context.span();
+
+        match response {
+            Ok(response) => {
+                span.set_attributes([
+                    KeyValue::new("ai.prompt_tokens", response.usage.prompt_tokens),
+                    KeyValue::new("ai.completion_tokens", response.usage.completion_tokens),
+                ]);
+
+                if let Some(choice) = response.choices.first() {
+                    span.set_attributes([
+                        KeyValue::new(
+                            "ai.finish_reason",
+                            choice.finish_reason.as_str().to_string(),
+                        ),
+                        KeyValue::new(
+                            "ai.native_finish_reason",
+                            choice.native_finish_reason.as_str().to_string(),
+                        ),
+                    ]);
+                }
+            }
+            Err(err) => span.set_status(Status::error(err.to_string())),
+        }
+
+        span.end();
+    }
+
+    /// Records the usage and the finish reason of the response and ends the span.
+    ///
+    /// # Arguments
+    /// * `response` - The result of the request.
+    #[cfg(not(feature = "otel"))]
+    #[inline(always)]
+    pub(crate) fn end(self, _response: &Result<ChatCompletionResponse>) {}
+}
+
+/// Injects the W3C `traceparent` and `tracestate` headers of the current context into the
+/// given request.
+///
+/// # Arguments
+/// * `request` - The request to add the headers to.
+#[cfg(feature = "otel")]
+pub(crate) fn inject_trace_context(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let mut headers = reqwest::header::HeaderMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(
+            &Context::current(),
+            &mut opentelemetry_http::HeaderInjector(&mut headers),
+        )
+    });
+
+    request.headers(headers)
+}
+
+/// Injects the W3C `traceparent` and `tracestate` headers of the current context into the
+/// given request.
+///
+/// # Arguments
+/// * `request` - The request to add the headers to.
+#[cfg(not(feature = "otel"))]
+#[inline(always)]
+pub(crate) fn inject_trace_context(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    request
+}
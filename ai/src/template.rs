@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::{Error, Result};
+
+/// The opening delimiter of a placeholder.
+const PLACEHOLDER_START: &str = "{{";
+
+/// The closing delimiter of a placeholder.
+const PLACEHOLDER_END: &str = "}}";
+
+/// A prompt with `{{key}}` placeholders, e.g., for system prompts with dynamic values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    pub template: String,
+}
+
+impl PromptTemplate {
+    /// Creates a new prompt template.
+    ///
+    /// # Arguments
+    /// * `template` - The prompt with `{{key}}` placeholders.
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// Replaces the placeholders with the values of the given variables.
+    /// Whitespace around the keys is ignored, i.e., `{{ key }}` equals `{{key}}`.
+    /// Fails with `Error::MissingTemplateVariable` if a placeholder has no value.
+    ///
+    /// # Arguments
+    /// * `vars` - The values of the variables by their keys.
+    pub fn render(&self, vars: &HashMap<&str, &str>) -> Result<String> {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find(PLACEHOLDER_START) {
+            let after_start = &rest[start + PLACEHOLDER_START.len()..];
+            let Some(end) = after_start.find(PLACEHOLDER_END) else {
+                break;
+            };
+
+            let key = after_start[..end].trim();
+            let value = vars
+                .get(key)
+                .ok_or_else(|| Error::MissingTemplateVariable(key.to_string()))?;
+
+            rendered.push_str(&rest[..start]);
+            rendered.push_str(value);
+            rest = &after_start[end + PLACEHOLDER_END.len()..];
+        }
+
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{Message, Role};
+
+    #[test]
+    fn test_render() {
+        let template = PromptTemplate::new("Hello {{user_name}}, today is {{ current_date }}.");
+        let vars = HashMap::from([("user_name", "Ada"), ("current_date", "2025-06-01")]);
+        assert_eq!(
+            template.render(&vars).unwrap(),
+            "Hello Ada, today is 2025-06-01."
+        );
+
+        let vars = HashMap::from([("user_name", "Ada")]);
+        assert!(matches!(
+            template.render(&vars),
+            Err(Error::MissingTemplateVariable(key)) if key == "current_date"
+        ));
+
+        let template = PromptTemplate::new("No placeholders, but {{ unterminated");
+        assert_eq!(
+            template.render(&HashMap::new()).unwrap(),
+            "No placeholders, but {{ unterminated"
+        );
+    }
+
+    #[test]
+    fn test_system_from_template() {
+        let template = PromptTemplate::new("You can use {{available_tools}}.");
+        let vars = HashMap::from([("available_tools", "get_weather")]);
+
+        let message = Message::system_from_template(&template, &vars).unwrap();
+        assert_eq!(message.role, Role::System);
+        assert_eq!(message.content.to_text(), "You can use get_weather.");
+    }
+}
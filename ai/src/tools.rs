@@ -1,25 +1,40 @@
+use std::collections::HashMap;
+use std::future::Future;
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::time::Duration;
 
 use schemars::Schema;
-use schemars::transform::AddNullable;
+use schemars::transform::{Transform, transform_subschemas};
 use schemars::{JsonSchema, generate::SchemaSettings};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
-use crate::{JsonFunctionInfo, JsonTool};
+use crate::platform;
+use crate::{
+    ChatCompletionParameter, Choice, ClientTrait, Error, FinishReason, JsonFunctionInfo, JsonTool,
+    JsonToolCall, Message, Result,
+};
 
 /// The description of a tool to be used in the chat completion request.
-pub struct Tool<P: JsonSchema> {
+/// The parameters of the tool are given by `P` and the optional output by `R`.
+pub struct Tool<P: JsonSchema, R: JsonSchema = ()> {
     name: String,
     description: String,
+    nullable_strategy: NullableStrategy,
     _p: PhantomData<P>,
+    _r: PhantomData<R>,
 }
 
-impl<P: JsonSchema> Tool<P> {
+impl<P: JsonSchema, R: JsonSchema> Tool<P, R> {
     /// Creates a new tool with the given name, description, and parameters.
     pub fn new(name: String, description: String) -> Self {
         Tool {
             name,
             description,
+            nullable_strategy: NullableStrategy::default(),
             _p: PhantomData,
+            _r: PhantomData,
         }
     }
 
@@ -33,9 +48,37 @@ impl<P: JsonSchema> Tool<P> {
         &self.description
     }
 
+    /// Sets the strategy for representing optional fields in the schemas of the tool.
+    ///
+    /// # Arguments
+    /// * `strategy` - The strategy for optional fields.
+    pub fn with_nullable_strategy(mut self, strategy: NullableStrategy) -> Self {
+        self.nullable_strategy = strategy;
+        self
+    }
+
+    /// Returns the strategy for representing optional fields in the schemas of the tool.
+    pub fn nullable_strategy(&self) -> NullableStrategy {
+        self.nullable_strategy
+    }
+
+    /// Returns true if the tool declares an output type, i.e., `R` is not `()`.
+    pub fn has_output_schema() -> bool {
+        has_output_schema::<R>()
+    }
+
     /// Converts the tool into a JSON representation.
     pub fn into_json(self) -> JsonTool {
-        let parameters = create_parameters_schema::<P>();
+        let strategy = self.nullable_strategy;
+        let mut parameters = create_parameters_schema::<P>(strategy);
+        let mut output_schema =
+            Self::has_output_schema().then(|| create_parameters_schema::<R>(strategy));
+
+        // strict mode validation of many providers does not resolve references
+        normalize_schema_inline_refs(&mut parameters);
+        if let Some(output_schema) = output_schema.as_mut() {
+            normalize_schema_inline_refs(output_schema);
+        }
 
         JsonTool {
             tool_type: "function".to_string(),
@@ -43,15 +86,495 @@ impl<P: JsonSchema> Tool<P> {
                 name: self.name,
                 description: self.description,
                 parameters,
+                output_schema,
                 strict: true,
             },
         }
     }
 }
 
+/// The strategy for representing optional fields, i.e., `Option<T>`, in the tool schemas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullableStrategy {
+    /// Adds `"null"` to the type array, e.g., `"type": ["string", "null"]`, and marks all
+    /// fields as required, as expected by strict mode.
+    #[default]
+    AddNull,
+
+    /// Wraps the type as `"anyOf": [{"type": "string"}, {"type": "null"}]` and marks all
+    /// fields as required.
+    AnyOf,
+
+    /// Keeps exactly one type string per field and omits optional fields from `required`.
+    Omit,
+}
+
+impl Transform for NullableStrategy {
+    fn transform(&mut self, schema: &mut Schema) {
+        if let Some(object) = schema.as_object_mut() {
+            match self {
+                NullableStrategy::AddNull => require_all_properties(object),
+                NullableStrategy::AnyOf => {
+                    wrap_null_type_in_any_of(object);
+                    require_all_properties(object);
+                }
+                NullableStrategy::Omit => remove_null_type(object),
+            }
+        }
+
+        transform_subschemas(self, schema);
+    }
+}
+
+/// Marks all properties of the given object schema as required.
+///
+/// # Arguments
+/// * `schema` - The schema to update.
+fn require_all_properties(schema: &mut serde_json::Map<String, Value>) {
+    if let Some(Value::Object(properties)) = schema.get("properties") {
+        let required = properties.keys().cloned().map(Value::String).collect();
+        schema.insert("required".to_string(), Value::Array(required));
+    }
+}
+
+/// Removes `"null"` from the type array of the given schema and returns true if it was present.
+///
+/// # Arguments
+/// * `schema` - The schema to update.
+fn remove_null_from_type_array(schema: &mut serde_json::Map<String, Value>) -> bool {
+    let Some(Value::Array(types)) = schema.get_mut("type") else {
+        return false;
+    };
+
+    let num_types = types.len();
+    types.retain(|t| t != "null");
+    if types.len() == num_types || types.is_empty() {
+        return false;
+    }
+
+    if types.len() == 1 {
+        let single_type = types.remove(0);
+        schema.insert("type".to_string(), single_type);
+    }
+
+    true
+}
+
+/// Replaces a type array containing `"null"` by an `anyOf` of the remaining type and null.
+///
+/// # Arguments
+/// * `schema` - The schema to update.
+fn wrap_null_type_in_any_of(schema: &mut serde_json::Map<String, Value>) {
+    if !remove_null_from_type_array(schema) {
+        return;
+    }
+
+    let mut non_null = std::mem::take(schema);
+    if let Some(description) = non_null.remove("description") {
+        schema.insert("description".to_string(), description);
+    }
+
+    let null = serde_json::json!({ "type": "null" });
+    schema.insert(
+        "anyOf".to_string(),
+        Value::Array(vec![Value::Object(non_null), null]),
+    );
+}
+
+/// Removes the null type from the type array or the `anyOf` of the given schema.
+///
+/// # Arguments
+/// * `schema` - The schema to update.
+fn remove_null_type(schema: &mut serde_json::Map<String, Value>) {
+    remove_null_from_type_array(schema);
+
+    if let Some(Value::Array(any_of)) = schema.get_mut("anyOf") {
+        let null = serde_json::json!({ "type": "null" });
+        if any_of.len() > 1 {
+            any_of.retain(|s| *s != null);
+        }
+    }
+}
+
 /// Creates a JSON schema for the given type `P`.
-pub fn create_parameters_schema<P: JsonSchema>() -> Schema {
-    let settings = SchemaSettings::default().with_transform(AddNullable::default());
+///
+/// # Arguments
+/// * `strategy` - The strategy for representing optional fields.
+pub fn create_parameters_schema<P: JsonSchema>(strategy: NullableStrategy) -> Schema {
+    let settings = SchemaSettings::default().with_transform(strategy);
     let generator = settings.into_generator();
     generator.into_root_schema_for::<P>()
 }
+
+/// Replaces every reference `{"$ref": "#/$defs/Foo"}` in the given schema by the inlined
+/// definition and removes the `$defs` section afterwards.
+/// References of recursive types cannot be inlined and are kept together with their definitions.
+///
+/// # Arguments
+/// * `schema` - The schema to normalize.
+pub fn normalize_schema_inline_refs(schema: &mut Schema) {
+    let Some(object) = schema.as_object_mut() else {
+        return;
+    };
+
+    let Some(Value::Object(definitions)) = object.remove("$defs") else {
+        return;
+    };
+
+    let mut unresolved = serde_json::Map::new();
+    for value in object.values_mut() {
+        inline_refs(value, &definitions, &mut Vec::new(), &mut unresolved);
+    }
+
+    if !unresolved.is_empty() {
+        object.insert("$defs".to_string(), Value::Object(unresolved));
+    }
+}
+
+/// Recursively inlines the references of the given value.
+///
+/// # Arguments
+/// * `value` - The value whose references are inlined.
+/// * `definitions` - The definitions of the root schema.
+/// * `stack` - The names of the definitions currently being inlined.
+/// * `unresolved` - The definitions of the references kept due to recursion.
+fn inline_refs(
+    value: &mut Value,
+    definitions: &serde_json::Map<String, Value>,
+    stack: &mut Vec<String>,
+    unresolved: &mut serde_json::Map<String, Value>,
+) {
+    match value {
+        Value::Object(object) => {
+            let name = object
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|r| r.strip_prefix("#/$defs/"))
+                .map(str::to_string);
+
+            if let Some(name) = name
+                && let Some(definition) = definitions.get(&name)
+            {
+                if stack.contains(&name) {
+                    if !unresolved.contains_key(&name) {
+                        unresolved.insert(name.clone(), Value::Null);
+
+                        let mut definition = definition.clone();
+                        inline_refs(&mut definition, definitions, stack, unresolved);
+                        unresolved.insert(name, definition);
+                    }
+
+                    return;
+                }
+
+                object.remove("$ref");
+                if let Value::Object(definition) = definition {
+                    for (key, value) in definition {
+                        object.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                }
+
+                stack.push(name);
+                for value in object.values_mut() {
+                    inline_refs(value, definitions, stack, unresolved);
+                }
+                stack.pop();
+
+                return;
+            }
+
+            for value in object.values_mut() {
+                inline_refs(value, definitions, stack, unresolved);
+            }
+        }
+        Value::Array(values) => {
+            for value in values.iter_mut() {
+                inline_refs(value, definitions, stack, unresolved);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns true if the output type `R` of a tool is not `()`.
+fn has_output_schema<R: JsonSchema>() -> bool {
+    R::schema_id() != <()>::schema_id()
+}
+
+/// Checks that the result of the tool with the given name is parseable into `R`.
+///
+/// # Arguments
+/// * `tool_name` - The name of the tool.
+/// * `result` - The raw result of the tool.
+fn validate_output<R: DeserializeOwned>(tool_name: &str, result: &str) -> Result<()> {
+    serde_json::from_str::<R>(result).map_err(|e| {
+        Error::Deserialization(format!("Invalid output of tool {}: {}", tool_name, e))
+    })?;
+
+    Ok(())
+}
+
+/// The future returned by a tool handler.
+pub type ToolFuture = Pin<Box<dyn Future<Output = Result<String>> + Send>>;
+
+/// A handler getting the raw JSON arguments of a tool call and returning the tool result.
+type ToolHandler = Box<dyn Fn(String) -> ToolFuture + Send + Sync>;
+
+/// A registry of tools and their handlers used to dispatch the tool calls of a response.
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: Vec<JsonTool>,
+    handlers: HashMap<String, ToolHandler>,
+    default_timeout: Option<Duration>,
+    timeouts: HashMap<String, Duration>,
+}
+
+impl ToolRegistry {
+    /// Creates a new empty tool registry without a timeout for the handlers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the timeout for the handlers of all tools without their own timeout and returns
+    /// the updated registry.
+    ///
+    /// # Arguments
+    /// * `default_timeout` - The maximum time a handler may take, e.g., 30 seconds.
+    pub fn with_default_timeout(mut self, default_timeout: Duration) -> Self {
+        self.default_timeout = Some(default_timeout);
+        self
+    }
+
+    /// Registers a tool together with its handler like `register`, but fails the tool calls
+    /// with `Error::ToolTimeout` if the handler takes longer than the given timeout.
+    /// The timeout overrides the default timeout of the registry.
+    ///
+    /// # Arguments
+    /// * `tool` - The tool to register.
+    /// * `handler` - The handler getting the deserialized arguments of the tool call.
+    /// * `timeout` - The maximum time the handler may take.
+    pub fn register_with_timeout<P, R, F>(
+        &mut self,
+        tool: Tool<P, R>,
+        handler: F,
+        timeout: Duration,
+    ) where
+        P: JsonSchema + DeserializeOwned,
+        R: JsonSchema + DeserializeOwned + 'static,
+        F: Fn(P) -> ToolFuture + Send + Sync + 'static,
+    {
+        let name = tool.name().to_string();
+        self.register(tool, handler);
+        self.timeouts.insert(name, timeout);
+    }
+
+    /// Registers a tool together with its handler.
+    /// The arguments of the tool calls are deserialized into `P` before calling the handler.
+    /// If the tool declares an output type `R`, the results of the handler are validated
+    /// to be parseable into `R`.
+    /// An already registered tool with the same name is replaced.
+    ///
+    /// # Arguments
+    /// * `tool` - The tool to register.
+    /// * `handler` - The handler getting the deserialized arguments of the tool call.
+    pub fn register<P, R, F>(&mut self, tool: Tool<P, R>, handler: F)
+    where
+        P: JsonSchema + DeserializeOwned,
+        R: JsonSchema + DeserializeOwned + 'static,
+        F: Fn(P) -> ToolFuture + Send + Sync + 'static,
+    {
+        let name = tool.name().to_string();
+        let json_tool = tool.into_json();
+
+        self.tools.retain(|t| t.function.name != name);
+        self.tools.push(json_tool);
+
+        let tool_name = name.clone();
+        self.register_handler(name, move |arguments: String| {
+            match serde_json::from_str::<P>(&arguments) {
+                Ok(parameter) if has_output_schema::<R>() => {
+                    let result = handler(parameter);
+                    let tool_name = tool_name.clone();
+
+                    Box::pin(async move {
+                        let result = result.await?;
+                        validate_output::<R>(&tool_name, &result)?;
+                        Ok(result)
+                    })
+                }
+                Ok(parameter) => handler(parameter),
+                Err(e) => {
+                    let message = format!("Invalid arguments for tool {}: {}", tool_name, e);
+                    Box::pin(async move { Err(Error::Deserialization(message)) })
+                }
+            }
+        });
+    }
+
+    /// Adds all registered tools to the given chat completion parameter.
+    /// Fails if the parameter already contains a tool with the same name.
+    ///
+    /// # Arguments
+    /// * `params` - The parameter to add the tools to.
+    pub fn apply_to(&self, params: &mut ChatCompletionParameter<'_>) -> Result<()> {
+        for tool in self.tools.iter() {
+            params.add_json_tool(tool.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Registers a handler for the tool with the given name.
+    /// An already registered handler with the same name is replaced together with its timeout.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the tool.
+    /// * `handler` - The handler getting the raw JSON arguments of the tool call.
+    pub fn register_handler<F>(&mut self, name: String, handler: F)
+    where
+        F: Fn(String) -> ToolFuture + Send + Sync + 'static,
+    {
+        self.timeouts.remove(&name);
+        self.handlers.insert(name, Box::new(handler));
+    }
+
+    /// Returns true if a handler for the tool with the given name is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    /// Dispatches the given tool call to the registered handler and returns its result.
+    /// Fails with `Error::ToolTimeout` if the handler exceeds the timeout of the tool.
+    ///
+    /// # Arguments
+    /// * `call` - The tool call to dispatch.
+    pub async fn dispatch(&self, call: &JsonToolCall) -> Result<String> {
+        let name = &call.function_call.name;
+        let handler = self
+            .handlers
+            .get(name)
+            .ok_or_else(|| Error::ToolNotFound(name.clone()))?;
+
+        let result = handler(call.function_call.arguments.clone());
+        match self.timeouts.get(name).copied().or(self.default_timeout) {
+            Some(timeout) => {
+                platform::timeout(timeout, result)
+                    .await
+                    .ok_or_else(|| Error::ToolTimeout {
+                        tool_name: name.clone(),
+                        elapsed: timeout,
+                    })?
+            }
+            None => result.await,
+        }
+    }
+}
+
+/// Dispatches all the given tool calls one after another and returns the pairs of
+/// tool call id and result in the same order as the calls.
+///
+/// # Arguments
+/// * `calls` - The tool calls to dispatch.
+/// * `handlers` - The registry with the tool handlers.
+pub async fn dispatch_all_tool_calls(
+    calls: &[JsonToolCall],
+    handlers: &ToolRegistry,
+) -> Vec<(String, Result<String>)> {
+    execute_sequential(calls, handlers).await
+}
+
+/// Dispatches the given tool calls strictly one after another, e.g., if a call depends on
+/// the side effects of the previous ones, and returns the pairs of tool call id and result
+/// in the same order as the calls.
+/// A failed call does not abort the subsequent calls.
+///
+/// # Arguments
+/// * `calls` - The tool calls to dispatch.
+/// * `registry` - The registry with the tool handlers.
+pub async fn execute_sequential(
+    calls: &[JsonToolCall],
+    registry: &ToolRegistry,
+) -> Vec<(String, Result<String>)> {
+    let mut results = Vec::with_capacity(calls.len());
+
+    for call in calls {
+        let result = registry.dispatch(call).await;
+        results.push((call.id.clone(), result));
+    }
+
+    results
+}
+
+/// Dispatches all the given tool calls concurrently and returns the pairs of tool call id
+/// and result in the same order as the calls.
+/// A failed call does not prevent the other calls from completing.
+///
+/// # Arguments
+/// * `calls` - The tool calls to dispatch.
+/// * `registry` - The registry with the tool handlers.
+pub async fn execute_parallel(
+    calls: &[JsonToolCall],
+    registry: &ToolRegistry,
+) -> Vec<(String, Result<String>)> {
+    let results = futures::future::join_all(calls.iter().map(|call| registry.dispatch(call))).await;
+
+    calls
+        .iter()
+        .map(|call| call.id.clone())
+        .zip(results)
+        .collect()
+}
+
+/// Runs the chat completion and dispatches the requested tool calls via the registry,
+/// until the model stops requesting tool calls.
+/// The assistant messages and the tool results are appended to the parameter.
+/// Returns the choices of the final response.
+///
+/// # Arguments
+/// * `client` - The client used for the chat completion requests.
+/// * `parameter` - The parameter for the chat completion requests.
+/// * `registry` - The registry used to dispatch the tool calls.
+/// * `max_iterations` - The maximum number of chat completion requests.
+pub async fn run_tool_loop<C: ClientTrait>(
+    client: &C,
+    parameter: &mut ChatCompletionParameter<'_>,
+    registry: &ToolRegistry,
+    max_iterations: usize,
+) -> Result<Vec<Choice>> {
+    for iteration in 0..max_iterations {
+        let choices = client.chat_completion(parameter).await?;
+
+        let Some(choice) = choices.first() else {
+            return Err(Error::InternalError(
+                "The response did not contain any choices".to_string(),
+            ));
+        };
+
+        if choice.finish_reason != FinishReason::ToolCalls && choice.message.tool_calls.is_empty() {
+            return Ok(choices);
+        }
+
+        log::debug!(
+            "Iteration {}: dispatching {} tool calls",
+            iteration,
+            choice.message.tool_calls.len()
+        );
+
+        let tool_calls = choice.message.tool_calls.clone();
+        parameter.add_message(choice.message.clone());
+
+        for (tool_call_id, result) in dispatch_all_tool_calls(&tool_calls, registry).await {
+            let content = match result {
+                Ok(content) => content,
+                Err(err) => {
+                    log::warn!("Tool call {} failed: {}", tool_call_id, err);
+                    format!("Error: {}", err)
+                }
+            };
+
+            parameter.add_message(Message::tool_result(tool_call_id, content));
+        }
+    }
+
+    Err(Error::InternalError("max iterations reached".to_string()))
+}
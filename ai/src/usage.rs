@@ -0,0 +1,57 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::Usage;
+
+/// The usage of a single successful chat completion request.
+#[derive(Debug, Clone)]
+pub struct UsageEvent {
+    /// The model that answered the request.
+    pub model: String,
+
+    /// The token usage reported by the API.
+    pub usage: Usage,
+
+    /// The id of the response.
+    pub response_id: String,
+
+    /// The duration of the request, including retries.
+    pub duration: Duration,
+}
+
+/// The callback called with the usage after each successful chat completion request.
+pub type UsageCallback = Arc<dyn Fn(UsageEvent) + Send + Sync>;
+
+/// The accumulated token usage of multiple chat completion requests.
+#[derive(Debug, Clone, Default)]
+pub struct UsageTracker {
+    /// The number of tracked requests.
+    pub requests: usize,
+
+    /// The total number of prompt tokens.
+    pub prompt_tokens: i64,
+
+    /// The total number of generated tokens.
+    pub completion_tokens: i64,
+
+    /// The total number of tokens.
+    pub total_tokens: i64,
+}
+
+impl UsageTracker {
+    /// Creates a new tracker without any usage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the usage of a single request.
+    ///
+    /// # Arguments
+    /// * `usage` - The usage reported by the API.
+    pub fn add(&mut self, usage: &Usage) {
+        self.requests += 1;
+        self.prompt_tokens += usage.prompt_tokens;
+        self.completion_tokens += usage.completion_tokens;
+        self.total_tokens += usage.total_tokens;
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+use crate::middleware::is_sensitive_header;
+use crate::{Error, Result};
+
+/// The environment variable enabling the recording mode, if set to "1".
+pub const RECORD_ENV_VAR: &str = "RS_AI_RECORD";
+
+/// The placeholder replacing sensitive header values in recorded cassettes.
+const REDACTED: &str = "<REDACTED>";
+
+/// The mode of a cassette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcrMode {
+    /// Requests are sent and the interactions are recorded into the cassette file.
+    Record,
+
+    /// Requests are answered from the cassette file without any network access.
+    Playback,
+}
+
+/// A single recorded HTTP interaction.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Interaction {
+    pub url: String,
+    pub method: String,
+    pub request_headers: BTreeMap<String, String>,
+    pub request_body: Option<String>,
+    pub response_status: u16,
+    pub response_headers: BTreeMap<String, String>,
+    pub response_body: String,
+}
+
+/// The content of a cassette file.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// The state of a cassette.
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+}
+
+/// A cassette for recording HTTP interactions and playing them back in tests.
+/// In recording mode, the cassette is written when it is dropped.
+pub struct Cassette {
+    path: PathBuf,
+    mode: VcrMode,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// Creates a new cassette for the given file. In playback mode, the file is loaded.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the cassette file.
+    /// * `mode` - The mode of the cassette.
+    pub fn new(path: impl Into<PathBuf>, mode: VcrMode) -> Result<Self> {
+        let path = path.into();
+
+        let interactions = match mode {
+            VcrMode::Record => Vec::new(),
+            VcrMode::Playback => {
+                let content = std::fs::read_to_string(&path).map_err(|e| {
+                    log::error!("Failed to read cassette {}: {}", path.display(), e);
+                    Error::IO(Box::new(e))
+                })?;
+
+                let file: CassetteFile = serde_json::from_str(&content).map_err(|e| {
+                    log::error!("Failed to parse cassette {}: {}", path.display(), e);
+                    Error::Deserialization(e.to_string())
+                })?;
+
+                file.interactions
+            }
+        };
+
+        let used = vec![false; interactions.len()];
+
+        Ok(Self {
+            path,
+            mode,
+            state: Mutex::new(CassetteState { interactions, used }),
+        })
+    }
+
+    /// Creates a cassette in the given directory named after the current test.
+    /// The recording mode is enabled by setting the environment variable `RS_AI_RECORD=1`,
+    /// otherwise the cassette is played back.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory of the cassette files, e.g., `tests/cassettes`.
+    pub fn for_current_test(dir: impl AsRef<Path>) -> Result<Self> {
+        let thread = std::thread::current();
+        let name = thread
+            .name()
+            .ok_or_else(|| Error::InternalError("The current thread has no name".to_string()))?;
+
+        let file_name = format!("{}.json", name.replace("::", "_"));
+        let path = dir.as_ref().join(file_name);
+
+        let mode = if std::env::var(RECORD_ENV_VAR).is_ok_and(|v| v == "1") {
+            VcrMode::Record
+        } else {
+            VcrMode::Playback
+        };
+
+        Self::new(path, mode)
+    }
+
+    /// Returns the mode of the cassette.
+    pub fn mode(&self) -> VcrMode {
+        self.mode
+    }
+
+    /// Writes the recorded interactions to the cassette file.
+    pub fn save(&self) -> Result<()> {
+        let state = self.state.lock().unwrap();
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+
+        let content =
+            serde_json::to_string_pretty(&file).map_err(|e| Error::InternalError(e.to_string()))?;
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::IO(Box::new(e)))?;
+        }
+
+        std::fs::write(&self.path, content).map_err(|e| {
+            log::error!("Failed to write cassette {}: {}", self.path.display(), e);
+            Error::IO(Box::new(e))
+        })
+    }
+
+    /// Executes the given request according to the mode of the cassette.
+    ///
+    /// # Arguments
+    /// * `client` - The HTTP client used in recording mode.
+    /// * `request` - The request to execute.
+    pub(crate) async fn execute(
+        &self,
+        client: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response> {
+        match self.mode {
+            VcrMode::Record => self.record(client, request).await,
+            VcrMode::Playback => self.playback(&request),
+        }
+    }
+
+    /// Sends the given request and records the interaction.
+    async fn record(
+        &self,
+        client: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response> {
+        let url = request.url().to_string();
+        let method = request.method().to_string();
+        let request_headers = headers_to_map(request.headers(), true);
+        let request_body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned());
+
+        let response = client.execute(request).await.map_err(|e| {
+            log::error!("Request failed: {}", e);
+            Error::HTTPError(Box::new(e))
+        })?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(|e| {
+            log::error!("Failed to read response body: {}", e);
+            Error::HTTPError(Box::new(e))
+        })?;
+
+        let interaction = Interaction {
+            url,
+            method,
+            request_headers,
+            request_body,
+            response_status: status.as_u16(),
+            response_headers: headers_to_map(&headers, false),
+            response_body: String::from_utf8_lossy(&body).into_owned(),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.interactions.push(interaction);
+        state.used.push(true);
+
+        build_response(
+            status.as_u16(),
+            &headers_to_map(&headers, false),
+            body.to_vec(),
+        )
+    }
+
+    /// Answers the given request with the first unused matching interaction.
+    fn playback(&self, request: &reqwest::Request) -> Result<reqwest::Response> {
+        let url = request.url().to_string();
+        let method = request.method().to_string();
+
+        let mut state = self.state.lock().unwrap();
+        let CassetteState { interactions, used } = &mut *state;
+
+        let (interaction, used) = interactions
+            .iter()
+            .zip(used.iter_mut())
+            .find(|(interaction, used)| {
+                !**used && interaction.method == method && interaction.url == url
+            })
+            .ok_or_else(|| {
+                Error::InternalError(format!(
+                    "No recorded interaction for {} {} in cassette {}",
+                    method,
+                    url,
+                    self.path.display()
+                ))
+            })?;
+
+        *used = true;
+
+        build_response(
+            interaction.response_status,
+            &interaction.response_headers,
+            interaction.response_body.clone().into_bytes(),
+        )
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        if self.mode == VcrMode::Record
+            && let Err(err) = self.save()
+        {
+            log::error!("Failed to save cassette: {}", err);
+        }
+    }
+}
+
+/// Converts the given headers into a map, optionally redacting sensitive values.
+///
+/// # Arguments
+/// * `headers` - The headers to convert.
+/// * `redact` - If true, sensitive values are replaced with a placeholder.
+fn headers_to_map(headers: &HeaderMap, redact: bool) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if redact && is_sensitive_header(name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Builds a response from the recorded status, headers and body.
+fn build_response(
+    status: u16,
+    headers: &BTreeMap<String, String>,
+    body: Vec<u8>,
+) -> Result<reqwest::Response> {
+    let mut builder = http::Response::builder().status(status);
+    for (name, value) in headers.iter() {
+        builder = builder.header(name, value);
+    }
+
+    let response = builder
+        .body(body)
+        .map_err(|e| Error::InternalError(format!("Failed to build response: {}", e)))?;
+
+    Ok(reqwest::Response::from(response))
+}
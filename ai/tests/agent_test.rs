@@ -0,0 +1,131 @@
+mod common;
+
+use ai::{ChatCompletionParameter, Error, Message, ReactAgent, Tool, ToolRegistry};
+use common::{chat_response, create_client};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct WeatherParameter {
+    /// City and country e.g. Bogotá, Colombia
+    pub location: String,
+}
+
+/// The final response of the model after receiving the tool results.
+const FINAL_RESPONSE: &str = r#"
+{
+    "id": "gen-2",
+    "created": 1747684760,
+    "choices": [
+        {
+            "finish_reason": "stop",
+            "native_finish_reason": "stop",
+            "index": 0,
+            "message": { "role": "assistant", "content": "It is sunny in London." }
+        }
+    ],
+    "usage": { "prompt_tokens": 90, "completion_tokens": 7, "total_tokens": 97 }
+}
+"#;
+
+/// Mounts a mock answering the first request with a tool call and all further requests with
+/// the given body.
+///
+/// # Arguments
+/// * `server` - The mock server.
+/// * `body` - The body of the responses after the first one.
+async fn mount_responses(server: &MockServer, body: &str) {
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response())
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .with_priority(2)
+        .mount(server)
+        .await;
+}
+
+/// Creates an agent with the weather tool for the given mock server.
+///
+/// # Arguments
+/// * `server` - The mock server.
+/// * `max_iterations` - The maximum number of iterations of the agent.
+fn create_agent(server: &MockServer, max_iterations: usize) -> ReactAgent {
+    let client = create_client(server);
+
+    let mut registry = ToolRegistry::new();
+    registry.register(
+        Tool::<WeatherParameter>::new(
+            "get_weather".to_string(),
+            "Get current temperature for a given location.".to_string(),
+        ),
+        |parameter| Box::pin(async move { Ok(format!("Sunny in {}", parameter.location)) }),
+    );
+
+    ReactAgent::new(client, registry, max_iterations)
+}
+
+/// Creates a parameter with a single user message.
+fn create_parameter() -> ChatCompletionParameter<'static> {
+    ChatCompletionParameter::new(
+        "openai/gpt-4.1".to_string(),
+        vec![Message::user("What is the weather like in London?")],
+    )
+}
+
+#[tokio::test]
+async fn test_react_agent_run() {
+    let server = MockServer::start().await;
+    mount_responses(&server, FINAL_RESPONSE).await;
+
+    let agent = create_agent(&server, 5);
+    let result = agent.run(create_parameter()).await.unwrap();
+
+    assert_eq!(result.final_content, "It is sunny in London.");
+    assert_eq!(result.iterations, 2);
+    assert_eq!(result.tool_calls.len(), 1);
+    assert_eq!(result.tool_calls[0].function_call.name, "get_weather");
+    assert_eq!(result.usage.requests, 2);
+    assert_eq!(result.usage.prompt_tokens, 64 + 90);
+    assert_eq!(result.usage.completion_tokens, 17 + 7);
+    assert_eq!(result.usage.total_tokens, 81 + 97);
+
+    // the second request must contain the tools, the assistant message and the tool result
+    let requests = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[2]["role"], "tool");
+    assert_eq!(messages[2]["content"], "Sunny in London, United Kingdom");
+}
+
+#[tokio::test]
+async fn test_react_agent_max_iterations() {
+    let server = MockServer::start().await;
+    mount_responses(
+        &server,
+        include_str!("../test_data/weather_tool_response.json"),
+    )
+    .await;
+
+    let agent = create_agent(&server, 2).with_sequential_tool_calls();
+    match agent.run(create_parameter()).await {
+        Err(Error::MaxIterationsReached { history }) => {
+            // the user message and two pairs of assistant message and tool result
+            assert_eq!(history.len(), 5);
+        }
+        other => panic!("Unexpected result: {:?}", other.map(|r| r.final_content)),
+    }
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
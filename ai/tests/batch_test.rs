@@ -0,0 +1,89 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use ai::{ChatCompletionParameter, Error, Middleware, MiddlewareFuture, Next, PreparedRequest};
+use common::{chat_response, create_client, create_parameter};
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A middleware tracking the maximum number of requests in flight at the same time.
+struct InFlightMiddleware {
+    in_flight: Arc<AtomicUsize>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+impl Middleware for InFlightMiddleware {
+    fn handle<'a>(&'a self, request: PreparedRequest, next: &'a dyn Next) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(current, Ordering::SeqCst);
+            let response = next.run(request).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            response
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_chat_completion_batch() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(
+            serde_json::json!({"model": "broken/model"}),
+        ))
+        .respond_with(ResponseTemplate::new(400).set_body_string("unknown model"))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response().set_delay(Duration::from_millis(20)))
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server);
+
+    let params = ["openai/gpt-4.1", "broken/model", "openai/gpt-4.1"]
+        .into_iter()
+        .map(|model| ChatCompletionParameter::new(model.to_string(), vec![]))
+        .collect();
+
+    let results = client.chat_completion_batch(params, 2).await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().len(), 1);
+    assert!(matches!(results[1], Err(Error::BadRequest(_))));
+    assert_eq!(results[2].as_ref().unwrap().len(), 1);
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    assert!(client.chat_completion_batch(Vec::new(), 2).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_chat_completion_batch_limits_concurrency() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response().set_delay(Duration::from_millis(50)))
+        .mount(&server)
+        .await;
+
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let client = create_client(&server).with_middleware(Box::new(InFlightMiddleware {
+        in_flight: Arc::new(AtomicUsize::new(0)),
+        max_in_flight: max_in_flight.clone(),
+    }));
+
+    let params = (0..5).map(|_| create_parameter()).collect();
+
+    let results = client.chat_completion_batch(params, 2).await;
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    assert_eq!(server.received_requests().await.unwrap().len(), 5);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::sync::Arc;
+
+use ai::{Budget, ChatCompletionParameter, Error, Message, Role};
+use common::{create_client, mount_chat_response};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Starts a mock server serving the models and chat completions.
+async fn start_server() -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("../test_data/models.json")),
+        )
+        .mount(&server)
+        .await;
+
+    // the usage of the response is 64 prompt and 17 completion tokens
+    mount_chat_response(&server).await;
+
+    server
+}
+
+/// Creates a chat completion parameter for a model with known pricing.
+fn create_parameter() -> ChatCompletionParameter<'static> {
+    let message = Message {
+        role: Role::User,
+        content: "Hello".into(),
+        tool_call_id: String::new(),
+        tool_calls: vec![],
+        refusal: None,
+        reasoning: None,
+    };
+
+    ChatCompletionParameter::new("openai/codex-mini".to_string(), vec![message])
+}
+
+#[tokio::test]
+async fn test_budget_exceeded() {
+    let server = start_server().await;
+    let budget = Arc::new(Budget::new(0.0003));
+
+    let client = create_client(&server).with_budget(budget.clone());
+    client.get_models().await.unwrap();
+
+    // each request costs 64 * 0.0000015 + 17 * 0.000006 = 0.000198 USD
+    let parameter = create_parameter();
+    client.chat_completion(&parameter).await.unwrap();
+    assert!((budget.spent_usd() - 0.000198).abs() < 1e-9);
+
+    client.chat_completion(&parameter).await.unwrap();
+    assert!((budget.spent_usd() - 0.000396).abs() < 1e-9);
+
+    match client.chat_completion(&parameter).await {
+        Err(Error::BudgetExceeded { limit, current }) => {
+            assert_eq!(limit, 0.0003);
+            assert!(current > 0.000396);
+        }
+        other => panic!("Expected budget exceeded error, got {:?}", other),
+    }
+
+    let requests = server.received_requests().await.unwrap();
+    let chat_requests = requests
+        .iter()
+        .filter(|r| r.url.path() == "/chat/completions")
+        .count();
+    assert_eq!(chat_requests, 2);
+}
+
+#[test]
+fn test_budget_concurrent_spend() {
+    let budget = Arc::new(Budget::new(1.0));
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let budget = budget.clone();
+            std::thread::spawn(move || {
+                for _ in 0..1000 {
+                    budget.add_spend(0.0001);
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert!((budget.spent_usd() - 0.4).abs() < 1e-9);
+    assert!((budget.remaining_usd() - 0.6).abs() < 1e-9);
+    assert!(budget.check(0.5).is_ok());
+    assert!(budget.check(0.7).is_err());
+}
+
+#[tokio::test]
+async fn test_budget_unknown_price() {
+    let server = MockServer::start().await;
+
+    // the prices of the model are unknown
+    let mut models: serde_json::Value =
+        serde_json::from_str(include_str!("../test_data/models.json")).unwrap();
+    for model in models["data"].as_array_mut().unwrap() {
+        if model["id"] == "openai/codex-mini" {
+            model["pricing"]["prompt"] = "unknown".into();
+            model["pricing"]["completion"] = "unknown".into();
+        }
+    }
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(models))
+        .mount(&server)
+        .await;
+
+    mount_chat_response(&server).await;
+
+    let budget = Arc::new(Budget::new(0.0003));
+    let client = create_client(&server).with_budget(budget.clone());
+    client.get_models().await.unwrap();
+
+    // the budget is not enforced for the model, but the requests succeed
+    let parameter = create_parameter();
+    client.chat_completion(&parameter).await.unwrap();
+    client.chat_completion(&parameter).await.unwrap();
+    assert_eq!(budget.spent_usd(), 0.0);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "cache")]
+
+mod common;
+
+use std::time::Duration;
+
+use ai::{ChatCompletionParameter, Message};
+use common::{create_client, create_parameter, mount_chat_response};
+use wiremock::MockServer;
+
+/// Starts a mock server answering every chat completion request with the weather response.
+async fn start_server() -> MockServer {
+    let server = MockServer::start().await;
+
+    mount_chat_response(&server).await;
+
+    server
+}
+
+#[tokio::test]
+async fn test_identical_requests_are_cached() {
+    let server = start_server().await;
+
+    let client = create_client(&server).with_response_cache(10, Duration::from_secs(60));
+
+    let mut parameter = ChatCompletionParameter::new(
+        "openai/gpt-4.1".to_string(),
+        vec![Message::user("What is the weather in Berlin?")],
+    )
+    .with_temperature(0.0)
+    .unwrap();
+    parameter.set_seed(42);
+
+    let first = client.chat_completion(&parameter).await.unwrap();
+    let second = client.chat_completion(&parameter).await.unwrap();
+    assert_eq!(first.len(), second.len());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    // a different seed is a different request
+    parameter.set_seed(7);
+    client.chat_completion(&parameter).await.unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+    // as are all other settings of the request body
+    parameter.set_max_tokens(16);
+    client.chat_completion(&parameter).await.unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_expired_responses_are_not_used() {
+    let server = start_server().await;
+
+    let client = create_client(&server).with_response_cache(10, Duration::ZERO);
+
+    let parameter = create_parameter();
+    client.chat_completion(&parameter).await.unwrap();
+    client.chat_completion(&parameter).await.unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
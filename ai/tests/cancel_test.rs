@@ -0,0 +1,39 @@
+mod common;
+
+use std::time::Duration;
+
+use ai::Error;
+use common::{chat_response, create_client, create_parameter};
+use tokio_util::sync::CancellationToken;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer};
+
+#[tokio::test]
+async fn test_chat_completion_cancelled() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response().set_delay(Duration::from_secs(10)))
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server);
+    let parameter = create_parameter();
+
+    let token = CancellationToken::new();
+    let cancel_token = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancel_token.cancel();
+    });
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.chat_completion_with_cancel(&parameter, token),
+    )
+    .await
+    .expect("The request was not cancelled");
+
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
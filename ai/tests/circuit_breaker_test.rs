@@ -0,0 +1,43 @@
+mod common;
+
+use std::time::Duration;
+
+use ai::{CircuitBreakerConfig, CircuitState, Error};
+use common::{create_client, create_parameter};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_circuit_opens_on_server_errors() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server).with_circuit_breaker(CircuitBreakerConfig {
+        failure_threshold: 2,
+        window: Duration::from_secs(60),
+        cooldown: Duration::from_secs(60),
+    });
+    assert_eq!(client.circuit_state(), CircuitState::Closed);
+
+    let parameter = create_parameter();
+    for _ in 0..2 {
+        assert!(matches!(
+            client.chat_completion(&parameter).await,
+            Err(Error::HTTPErrorWithStatusCode(_))
+        ));
+    }
+
+    assert!(matches!(client.circuit_state(), CircuitState::Open { .. }));
+    assert!(matches!(
+        client.chat_completion(&parameter).await,
+        Err(Error::CircuitOpen { .. })
+    ));
+
+    // the rejected request was not sent
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
@@ -0,0 +1,48 @@
+//! Helpers shared by the integration tests. Each test binary only uses some of them.
+#![allow(dead_code)]
+
+use ai::{ChatCompletionParameter, Client};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// The model of the requests in the tests.
+pub const MODEL: &str = "openai/gpt-4.1";
+
+/// Returns the API URL of the given mock server.
+///
+/// # Arguments
+/// * `server` - The mock server answering the requests.
+pub fn api_url(server: &MockServer) -> reqwest::Url {
+    format!("{}/", server.uri()).parse().unwrap()
+}
+
+/// Creates a client sending its requests to the given mock server.
+///
+/// # Arguments
+/// * `server` - The mock server answering the requests.
+pub fn create_client(server: &MockServer) -> Client {
+    Client::new("test-key".to_string(), api_url(server)).unwrap()
+}
+
+/// Creates the parameter of a chat completion request without messages.
+pub fn create_parameter() -> ChatCompletionParameter<'static> {
+    ChatCompletionParameter::new(MODEL.to_string(), vec![])
+}
+
+/// Returns the successful chat completion response with the weather tool call.
+pub fn chat_response() -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .set_body_string(include_str!("../../test_data/weather_tool_response.json"))
+}
+
+/// Answers every chat completion request of the given mock server with the weather tool call.
+///
+/// # Arguments
+/// * `server` - The mock server answering the requests.
+pub async fn mount_chat_response(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response())
+        .mount(server)
+        .await;
+}
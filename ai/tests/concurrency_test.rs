@@ -0,0 +1,79 @@
+mod common;
+
+use std::time::Duration;
+
+use ai::{Client, Error};
+use common::{chat_response, create_client, create_parameter};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer};
+
+/// Starts a server answering chat completion requests after the given delay.
+async fn start_server(delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response().set_delay(delay))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_queues_requests() {
+    let server = start_server(Duration::from_millis(100)).await;
+
+    let client = create_client(&server).with_concurrency_limit(1);
+
+    let parameter = create_parameter();
+
+    let start = std::time::Instant::now();
+    let (first, second) = tokio::join!(
+        client.chat_completion(&parameter),
+        client.chat_completion(&parameter)
+    );
+
+    assert!(first.is_ok());
+    assert!(second.is_ok());
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+/// Sends three concurrent requests with the given client, which must be limited to one
+/// concurrent request and one waiting request, and checks that the third request is rejected.
+async fn assert_queue_full(server: &MockServer, client: Client) {
+    let parameter = create_parameter();
+
+    let (first, second, third) = tokio::join!(
+        client.chat_completion(&parameter),
+        client.chat_completion(&parameter),
+        client.chat_completion(&parameter)
+    );
+
+    assert!(first.is_ok());
+    assert!(second.is_ok());
+    assert!(matches!(third, Err(Error::QueueFull)));
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_queue_full() {
+    let server = start_server(Duration::from_millis(100)).await;
+
+    let client = create_client(&server)
+        .with_concurrency_limit(1)
+        .with_max_queue_depth(1);
+
+    assert_queue_full(&server, client).await;
+}
+
+#[tokio::test]
+async fn test_queue_full_independent_of_builder_order() {
+    let server = start_server(Duration::from_millis(100)).await;
+
+    let client = create_client(&server)
+        .with_max_queue_depth(1)
+        .with_concurrency_limit(1);
+
+    assert_queue_full(&server, client).await;
+}
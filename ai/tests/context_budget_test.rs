@@ -0,0 +1,89 @@
+mod common;
+
+use ai::{
+    ChatCompletionParameter, ContextBudget, Error, Message, PruneStrategy, Role, make_choice,
+};
+use common::create_client;
+use wiremock::MockServer;
+
+/// Creates a conversation with a system prompt and alternating user and assistant messages,
+/// each with 40 characters, i.e., 10 tokens.
+fn create_messages() -> Vec<Message> {
+    let mut messages = vec![Message::system("s".repeat(40))];
+    for i in 0..3 {
+        messages.push(Message::user(format!("{}", i).repeat(40)));
+        messages.push(make_choice(&"a".repeat(40)).message);
+    }
+
+    messages
+}
+
+#[test]
+fn test_context_budget_check() {
+    let messages = create_messages();
+    assert_eq!(ContextBudget::estimate(&messages), 70);
+
+    assert!(ContextBudget::new(70).check(&messages).is_ok());
+    assert!(matches!(
+        ContextBudget::new(69).check(&messages),
+        Err(Error::ContextLengthExceeded {
+            max_tokens: Some(69),
+            used_tokens: Some(70)
+        })
+    ));
+}
+
+#[test]
+fn test_context_budget_prune_to_fit() {
+    // the tool result of the first assistant message is removed together with it
+    let mut messages = create_messages();
+    messages.insert(3, Message::tool_result("call_0", "t".repeat(40)));
+
+    let pruned = ContextBudget::new(50).prune_to_fit(&mut messages);
+    assert_eq!(pruned, 3);
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[0].role, Role::System);
+    assert_eq!(messages[1].content.to_text(), "1".repeat(40));
+
+    // the system prompt is never removed
+    let pruned = ContextBudget::new(1).prune_to_fit(&mut messages);
+    assert_eq!(pruned, 4);
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].role, Role::System);
+}
+
+#[tokio::test]
+async fn test_chat_completion_context_budget() {
+    let server = MockServer::start().await;
+    let client = create_client(&server);
+
+    let parameter = ChatCompletionParameter::new("openai/gpt-4.1".to_string(), create_messages())
+        .with_context_budget(ContextBudget::new(10));
+
+    assert!(matches!(
+        client.chat_completion(&parameter).await,
+        Err(Error::ContextLengthExceeded { .. })
+    ));
+    assert!(matches!(
+        client.chat_completion_stream(&parameter).await,
+        Err(Error::ContextLengthExceeded { .. })
+    ));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[test]
+fn test_prune_messages_oldest_first() {
+    let budget = ContextBudget::new(30).with_prune_strategy(PruneStrategy::OldestFirst);
+    let mut parameter =
+        ChatCompletionParameter::new("openai/gpt-4.1".to_string(), create_messages())
+            .with_context_budget(budget);
+
+    assert_eq!(parameter.prune_messages(&budget), 4);
+    assert!(budget.check(parameter.messages()).is_ok());
+
+    let roles: Vec<&Role> = parameter.messages().iter().map(|m| &m.role).collect();
+    assert_eq!(roles, [&Role::System, &Role::User, &Role::Assistant]);
+    assert_eq!(parameter.messages()[1].content.to_text(), "2".repeat(40));
+
+    assert_eq!(parameter.prune_messages(&budget), 0);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use ai::json_types::{JsonFunctionCall, JsonToolCall};
+use ai::{
+    ChatCompletionParameter, Choice, Conversation, Error, FinishReason, Message, MockClient, Role,
+    make_choice,
+};
+use common::create_parameter;
+
+/// Creates a choice requesting the weather tool.
+fn tool_call_choice() -> Choice {
+    let mut choice = make_choice("");
+    choice.finish_reason = FinishReason::ToolCalls;
+    choice.message.tool_calls = vec![JsonToolCall {
+        index: 0,
+        id: "call_0".to_string(),
+        r#type: "function".to_string(),
+        function_call: JsonFunctionCall {
+            name: "get_weather".to_string(),
+            arguments: "{\"location\":\"London\"}".to_string(),
+        },
+    }];
+
+    choice
+}
+
+/// Creates a mock client requesting a tool call unless the last message is a tool result.
+fn create_client() -> MockClient {
+    MockClient::new().on_request(|parameter| {
+        let last = parameter.messages().last().unwrap();
+        if last.role == Role::Tool {
+            Ok(vec![make_choice("It is sunny in London.")])
+        } else {
+            Ok(vec![tool_call_choice()])
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_conversation_with_tool_calls() {
+    let client = create_client();
+    let parameter = create_parameter();
+    let mut conversation = Conversation::new(parameter);
+
+    let turn = conversation
+        .send_user(&client, "What is the weather like in London?")
+        .await
+        .unwrap();
+    assert_eq!(turn.finish_reason, FinishReason::ToolCalls);
+    assert_eq!(turn.tool_calls().len(), 1);
+    assert!(conversation.is_waiting_for_tools());
+    assert_eq!(conversation.history().len(), 2);
+
+    // the conversation cannot continue before the tool calls are resolved
+    assert!(matches!(
+        conversation.send_user(&client, "Hello?").await,
+        Err(Error::InternalError(_))
+    ));
+    assert!(matches!(
+        conversation.resolve_tool_call("unknown", "sunny"),
+        Err(Error::ToolNotFound(_))
+    ));
+
+    conversation.resolve_tool_call("call_0", "sunny").unwrap();
+    assert!(!conversation.is_waiting_for_tools());
+
+    let turn = conversation.send(&client).await.unwrap();
+    assert_eq!(turn.content(), "It is sunny in London.");
+    assert!(!conversation.is_waiting_for_tools());
+
+    let roles: Vec<&Role> = conversation.history().iter().map(|m| &m.role).collect();
+    assert_eq!(
+        roles,
+        [&Role::User, &Role::Assistant, &Role::Tool, &Role::Assistant]
+    );
+    assert_eq!(conversation.history()[2].tool_call_id, "call_0");
+    assert_eq!(client.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_conversation_save_load() {
+    let client = create_client();
+    let parameter = ChatCompletionParameter::new(
+        "openai/gpt-4.1".to_string(),
+        vec![Message::system("Be brief.")],
+    );
+    let mut conversation = Conversation::new(parameter);
+    conversation
+        .send_user(&client, "What is the weather like in London?")
+        .await
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("conversation-{}.json", std::process::id()));
+    conversation.save(&path).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["model"], "openai/gpt-4.1");
+    assert_eq!(json["system_prompt"], "Be brief.");
+    assert_eq!(json["messages"].as_array().unwrap().len(), 2);
+    assert_eq!(json["pending_tool_calls"][0]["id"], "call_0");
+
+    let mut loaded = Conversation::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.parameter().model(), "openai/gpt-4.1");
+    assert_eq!(loaded.history().len(), 3);
+    assert_eq!(loaded.history()[0].role, Role::System);
+    assert!(loaded.is_waiting_for_tools());
+
+    loaded.resolve_tool_call("call_0", "sunny").unwrap();
+    let turn = loaded.send(&client).await.unwrap();
+    assert_eq!(turn.content(), "It is sunny in London.");
+}
+
+#[test]
+fn test_conversation_load_unsupported_version() {
+    let path = std::env::temp_dir().join(format!("conversation-v2-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{"version": 2, "model": "openai/gpt-4.1", "messages": []}"#,
+    )
+    .unwrap();
+
+    let result = Conversation::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(Error::Deserialization(_))));
+}
+
+#[test]
+fn test_conversation_load_legacy_history() {
+    let path = std::env::temp_dir().join(format!("conversation-v0-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"[
+            {"role": "system", "content": "Be brief."},
+            {"role": "user", "content": "Hello"},
+            {"role": "assistant", "content": "Hi!"}
+        ]"#,
+    )
+    .unwrap();
+
+    let conversation = Conversation::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(conversation.parameter().model(), "");
+    assert_eq!(conversation.history().len(), 3);
+    assert_eq!(conversation.history()[0].role, Role::System);
+    assert_eq!(conversation.history()[2].content.to_text(), "Hi!");
+    assert!(!conversation.is_waiting_for_tools());
+}
+
+#[tokio::test]
+async fn test_conversation_fork() {
+    let client = MockClient::new().on_request(|parameter| {
+        let last = parameter.messages().last().unwrap();
+        Ok(vec![make_choice(&format!(
+            "Echo: {}",
+            last.content.to_text()
+        ))])
+    });
+
+    let parameter = create_parameter().with_temperature(0.5);
+    let mut conversation = Conversation::new(parameter.unwrap());
+    conversation.send_user(&client, "Hello").await.unwrap();
+
+    let mut fork = conversation.fork();
+    let turn = fork.send_user(&client, "Branch").await.unwrap();
+    assert_eq!(turn.content(), "Echo: Branch");
+
+    assert_eq!(conversation.history().len(), 2);
+    assert_eq!(fork.history().len(), 4);
+
+    let turn = conversation.send_user(&client, "Original").await.unwrap();
+    assert_eq!(turn.content(), "Echo: Original");
+    assert_eq!(conversation.history().len(), 4);
+    assert_eq!(fork.history()[2].content.to_text(), "Branch");
+
+    let requests = client.requests();
+    assert_eq!(requests[1]["temperature"], 0.5);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use ai::Client;
+use common::{api_url, chat_response, create_client, create_parameter, mount_chat_response};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer};
+
+#[tokio::test]
+async fn test_extra_headers_are_sent() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Authorization", "Bearer test-key"))
+        .and(header("X-Title", "rs-ai"))
+        .and(header("HTTP-Referer", "https://example.com"))
+        .respond_with(chat_response())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server);
+
+    let mut parameter = create_parameter();
+    parameter
+        .add_header("X-Title", "rs-ai")
+        .add_header("HTTP-Referer", "https://example.com");
+
+    assert!(client.chat_completion(&parameter).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_injected_http_client() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Authorization", "Bearer test-key"))
+        .and(header("X-Injected", "yes"))
+        .respond_with(chat_response())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("X-Injected", "yes".parse().unwrap());
+    let http_client = reqwest::Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap();
+
+    // the injected client is kept, even if the HTTP settings are changed afterwards
+    let client = Client::from_reqwest_client("test-key".to_string(), api_url(&server), http_client)
+        .unwrap()
+        .with_user_agent("my-app/1.0")
+        .unwrap();
+
+    let parameter = create_parameter();
+    assert!(client.chat_completion(&parameter).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_user_agent() {
+    let server = MockServer::start().await;
+
+    mount_chat_response(&server).await;
+
+    let api_url = api_url(&server);
+    let parameter = create_parameter();
+
+    let client = Client::new("test-key".to_string(), api_url.clone()).unwrap();
+    client.chat_completion(&parameter).await.unwrap();
+
+    let client = Client::new("test-key".to_string(), api_url.clone())
+        .unwrap()
+        .with_user_agent("my-app/1.0")
+        .unwrap();
+    client.chat_completion(&parameter).await.unwrap();
+
+    let http_client = reqwest::Client::builder()
+        .user_agent("injected/1.0")
+        .build()
+        .unwrap();
+    let client = Client::from_reqwest_client("test-key".to_string(), api_url, http_client)
+        .unwrap()
+        .with_user_agent("my-app/1.0")
+        .unwrap();
+    client.chat_completion(&parameter).await.unwrap();
+
+    let user_agents: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.headers["User-Agent"].to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        user_agents,
+        [
+            format!("rs-ai/{}", env!("CARGO_PKG_VERSION")),
+            "my-app/1.0".to_string(),
+            "injected/1.0".to_string()
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_correlation_id() {
+    let server = MockServer::start().await;
+
+    mount_chat_response(&server).await;
+
+    let client = create_client(&server)
+        .with_default_correlation_id_generator(Box::new(|| "generated-id".to_string()));
+
+    let parameter = create_parameter();
+    client.chat_completion(&parameter).await.unwrap();
+
+    let parameter = parameter.with_correlation_id("my-id");
+    assert_eq!(parameter.correlation_id(), Some("my-id"));
+    client.chat_completion(&parameter).await.unwrap();
+
+    let request_ids: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            request.headers["X-Request-ID"]
+                .to_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(request_ids, ["generated-id", "my-id"]);
+}
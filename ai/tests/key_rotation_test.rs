@@ -0,0 +1,63 @@
+mod common;
+
+use std::time::Duration;
+
+use ai::{Client, Error, RotationStrategy};
+use common::{api_url, create_parameter, mount_chat_response};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_rejected_key_is_excluded() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Authorization", "Bearer revoked-key"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    mount_chat_response(&server).await;
+
+    let client = Client::new("unused-key".to_string(), api_url(&server))
+        .unwrap()
+        .with_api_keys(
+            vec!["revoked-key".to_string(), "valid-key".to_string()],
+            RotationStrategy::RoundRobin,
+        )
+        .with_key_cooldown(Duration::from_secs(60));
+
+    let parameter = create_parameter();
+    assert!(matches!(
+        client.chat_completion(&parameter).await,
+        Err(Error::HTTPErrorWithStatusCode(_))
+    ));
+
+    // the revoked key is excluded, so only the valid key is used
+    for _ in 0..3 {
+        client.chat_completion(&parameter).await.unwrap();
+    }
+
+    let keys: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            request.headers["Authorization"]
+                .to_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "Bearer revoked-key",
+            "Bearer valid-key",
+            "Bearer valid-key",
+            "Bearer valid-key"
+        ]
+    );
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use ai::{Client, Error};
+use common::{create_client, create_parameter, mount_chat_response};
+use wiremock::MockServer;
+
+/// Returns the value of the metric with the given name and label values.
+fn metric_value(registry: &prometheus::Registry, name: &str, labels: &[&str]) -> f64 {
+    let family = registry
+        .gather()
+        .into_iter()
+        .find(|family| family.name() == name)
+        .unwrap();
+
+    family
+        .get_metric()
+        .iter()
+        .find(|metric| {
+            let values: Vec<&str> = metric.get_label().iter().map(|l| l.value()).collect();
+            values == labels
+        })
+        .map(|metric| {
+            if family.get_field_type() == prometheus::proto::MetricType::HISTOGRAM {
+                metric.get_histogram().get_sample_count() as f64
+            } else {
+                metric.get_counter().get_value()
+            }
+        })
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_prometheus_metrics() {
+    let server = MockServer::start().await;
+
+    mount_chat_response(&server).await;
+
+    let registry = prometheus::Registry::new();
+    let client = create_client(&server)
+        .with_prometheus_registry(&registry)
+        .unwrap();
+
+    let parameter = create_parameter();
+    client.chat_completion(&parameter).await.unwrap();
+    client.chat_completion(&parameter).await.unwrap();
+
+    let model = "openai/gpt-4.1";
+    assert_eq!(
+        metric_value(&registry, "rs_ai_requests_total", &[model, "success"]),
+        2.0
+    );
+    assert_eq!(
+        metric_value(&registry, "rs_ai_request_duration_seconds", &[model]),
+        2.0
+    );
+    assert_eq!(
+        metric_value(&registry, "rs_ai_prompt_tokens_total", &[model]),
+        128.0
+    );
+    assert_eq!(
+        metric_value(&registry, "rs_ai_completion_tokens_total", &[model]),
+        34.0
+    );
+}
+
+#[test]
+fn test_prometheus_registry_already_registered() {
+    let registry = prometheus::Registry::new();
+    let api_url: reqwest::Url = "http://localhost/".parse().unwrap();
+
+    let client = Client::new("test-key".to_string(), api_url.clone()).unwrap();
+    assert!(client.with_prometheus_registry(&registry).is_ok());
+
+    let client = Client::new("test-key".to_string(), api_url).unwrap();
+    assert!(matches!(
+        client.with_prometheus_registry(&registry),
+        Err(Error::InternalError(_))
+    ));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ai::{Client, Middleware, MiddlewareFuture, Next, PreparedRequest, RawResponse};
+use common::{chat_response, create_client, create_parameter};
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, HeaderValue};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer};
+
+/// A middleware adding a header to each request and counting the requests.
+struct TagMiddleware {
+    tag: &'static str,
+    count: Arc<AtomicUsize>,
+}
+
+impl Middleware for TagMiddleware {
+    fn handle<'a>(
+        &'a self,
+        mut request: PreparedRequest,
+        next: &'a dyn Next,
+    ) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            self.count.fetch_add(1, Ordering::SeqCst);
+
+            // append the tag to the existing tags to check the order of the middlewares
+            let tags = match request.headers.get("X-Tags") {
+                Some(tags) => format!("{}|{}", tags.to_str().unwrap(), self.tag),
+                None => self.tag.to_string(),
+            };
+            request
+                .headers
+                .insert("X-Tags", HeaderValue::from_str(&tags).unwrap());
+
+            next.run(request).await
+        })
+    }
+}
+
+/// A middleware answering all requests without sending them.
+struct StubMiddleware;
+
+impl Middleware for StubMiddleware {
+    fn handle<'a>(
+        &'a self,
+        _request: PreparedRequest,
+        _next: &'a dyn Next,
+    ) -> MiddlewareFuture<'a> {
+        Box::pin(async move {
+            Ok(RawResponse {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: include_bytes!("../test_data/weather_tool_response.json").to_vec(),
+            })
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_middlewares_are_applied_in_order() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("X-Tags", "first|second"))
+        .respond_with(chat_response())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let client = create_client(&server)
+        .with_middleware(Box::new(TagMiddleware {
+            tag: "first",
+            count: count.clone(),
+        }))
+        .with_middleware(Box::new(TagMiddleware {
+            tag: "second",
+            count: count.clone(),
+        }));
+
+    let parameter = create_parameter();
+    assert!(client.chat_completion(&parameter).await.is_ok());
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_middleware_short_circuit() {
+    // the API host does not exist, so the request only succeeds if it is never sent
+    let api_url = "http://api.invalid/".parse().unwrap();
+    let client = Client::new("test-key".to_string(), api_url)
+        .unwrap()
+        .with_middleware(Box::new(StubMiddleware));
+
+    let parameter = create_parameter();
+    let choices = client.chat_completion(&parameter).await.unwrap();
+    assert!(!choices.is_empty());
+}
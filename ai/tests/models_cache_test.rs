@@ -0,0 +1,116 @@
+mod common;
+
+use std::time::Duration;
+
+use common::create_client;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Starts a mock server serving an empty list of models.
+async fn start_models_server() -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data": []}"#))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+/// Returns the number of requests the mock server received.
+async fn num_requests(server: &MockServer) -> usize {
+    server.received_requests().await.unwrap().len()
+}
+
+#[tokio::test]
+async fn test_models_cached_without_ttl() {
+    let server = start_models_server().await;
+    let client = create_client(&server);
+
+    client.get_models().await.unwrap();
+    client.get_models().await.unwrap();
+    assert_eq!(num_requests(&server).await, 1);
+
+    client.refresh_models().await.unwrap();
+    assert_eq!(num_requests(&server).await, 2);
+
+    client.invalidate_model_cache();
+    client.get_models().await.unwrap();
+    assert_eq!(num_requests(&server).await, 3);
+}
+
+#[tokio::test]
+async fn test_models_cache_ttl() {
+    let server = start_models_server().await;
+    let client = create_client(&server).with_model_cache_ttl(Duration::from_millis(50));
+
+    client.get_models().await.unwrap();
+    client.get_models().await.unwrap();
+    assert_eq!(num_requests(&server).await, 1);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    client.get_models().await.unwrap();
+    assert_eq!(num_requests(&server).await, 2);
+}
+
+#[tokio::test]
+async fn test_clones_share_models_cache() {
+    let server = start_models_server().await;
+    let client = create_client(&server);
+    let clone = client.clone();
+
+    // the clone can be moved into another task, as the client is Send and Sync
+    tokio::spawn(async move { clone.get_models().await.map(|_| ()) })
+        .await
+        .unwrap()
+        .unwrap();
+
+    client.get_models().await.unwrap();
+    assert_eq!(num_requests(&server).await, 1);
+}
+
+#[tokio::test]
+async fn test_models_revalidated_with_etag() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_string(include_str!("../test_data/models.json")),
+        )
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server).with_model_cache_ttl(Duration::from_millis(50));
+    let num_models = client.get_models().await.unwrap().get_models().len();
+    assert!(num_models > 0);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    // the expired models are kept, as the server reports them as unchanged
+    let models = client.get_models().await.unwrap();
+    assert_eq!(models.get_models().len(), num_models);
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].headers.contains_key("If-None-Match"));
+    assert_eq!(requests[1].headers["If-None-Match"], "\"v1\"");
+
+    // the refreshed cache is valid again without another request
+    client.get_models().await.unwrap();
+    assert_eq!(num_requests(&server).await, 2);
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::create_client;
+use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Creates the JSON body of a models page with the given model ids.
+fn models_page(ids: &[&str], next_cursor: Option<&str>) -> serde_json::Value {
+    let models: Vec<serde_json::Value> = ids
+        .iter()
+        .map(|id| {
+            serde_json::json!({
+                "id": id,
+                "name": id,
+                "created": 0,
+                "description": "",
+                "context_length": 8192,
+                "architecture": {
+                    "modality": "text->text",
+                    "input_modalities": ["text"],
+                    "output_modalities": ["text"],
+                    "tokenizer": "Other",
+                    "instruct_type": null
+                },
+                "pricing": {"prompt": "0", "completion": "0"},
+                "top_provider": {"is_moderated": false},
+                "per_request_limits": null,
+                "supported_parameters": []
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "data": models,
+        "has_more": next_cursor.is_some(),
+        "next_cursor": next_cursor,
+    })
+}
+
+/// Starts a mock server serving the models in two pages.
+async fn start_paginated_server() -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .and(query_param_is_missing("after"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(models_page(&["a/one", "a/two"], Some("c1"))),
+        )
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .and(query_param("after", "c1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(models_page(&["b/three"], None)))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn test_get_models_page() {
+    let server = start_paginated_server().await;
+    let client = create_client(&server);
+
+    let page = client.get_models_page(2, None).await.unwrap();
+    assert_eq!(page.models.len(), 2);
+    assert_eq!(page.next_cursor.as_deref(), Some("c1"));
+
+    let page = client.get_models_page(2, Some("c1")).await.unwrap();
+    assert_eq!(page.models.len(), 1);
+    assert_eq!(page.models[0].id, "b/three");
+    assert!(page.next_cursor.is_none());
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[0].url.query(), Some("limit=2"));
+}
+
+#[tokio::test]
+async fn test_get_all_models_follows_cursor() {
+    let server = start_paginated_server().await;
+    let client = create_client(&server);
+
+    let models = client.get_all_models().await.unwrap();
+    assert_eq!(models.get_models().len(), 3);
+
+    let models = client.get_models().await.unwrap();
+    let ids: Vec<&str> = models.get_models().iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["a/one", "a/two", "b/three"]);
+}
+
+#[tokio::test]
+async fn test_get_all_models_stops_at_cursor_cycle() {
+    let server = MockServer::start().await;
+
+    // the cursors form the cycle A -> B -> A
+    for (after, id, next_cursor) in [
+        (None, "a/one", "A"),
+        (Some("A"), "a/two", "B"),
+        (Some("B"), "b/three", "A"),
+    ] {
+        let mock = Mock::given(method("GET")).and(path("/models"));
+        let mock = match after {
+            Some(after) => mock.and(query_param("after", after)),
+            None => mock.and(query_param_is_missing("after")),
+        };
+
+        mock.respond_with(
+            ResponseTemplate::new(200).set_body_json(models_page(&[id], Some(next_cursor))),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    }
+
+    let client = create_client(&server);
+    let models = client.get_all_models().await.unwrap();
+    let ids: Vec<&str> = models.get_models().iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["a/one", "a/two", "b/three"]);
+}
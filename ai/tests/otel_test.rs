@@ -0,0 +1,67 @@
+#![cfg(feature = "otel")]
+
+mod common;
+
+use common::{create_client, create_parameter, mount_chat_response};
+use opentelemetry::Value;
+use opentelemetry::global;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use wiremock::MockServer;
+
+/// Returns the value of the attribute with the given key of the span.
+fn attribute(span: &SpanData, key: &str) -> Value {
+    span.attributes
+        .iter()
+        .find(|attribute| attribute.key.as_str() == key)
+        .map(|attribute| attribute.value.clone())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_trace_context_and_span_attributes() {
+    let server = MockServer::start().await;
+
+    mount_chat_response(&server).await;
+
+    let exporter = InMemorySpanExporter::default();
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(
+        SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build(),
+    );
+
+    let client = create_client(&server);
+
+    let parameter = create_parameter();
+    client.chat_completion(&parameter).await.unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    assert_eq!(spans.len(), 1);
+    let span = &spans[0];
+    assert_eq!(span.name, "ai.chat_completion");
+    assert_eq!(attribute(span, "ai.model"), Value::from("openai/gpt-4.1"));
+    assert_eq!(attribute(span, "ai.prompt_tokens"), Value::I64(64));
+    assert_eq!(attribute(span, "ai.completion_tokens"), Value::I64(17));
+    assert_eq!(
+        attribute(span, "ai.finish_reason"),
+        Value::from("tool_calls")
+    );
+    assert_eq!(
+        attribute(span, "ai.native_finish_reason"),
+        Value::from("tool_calls")
+    );
+
+    // the request is sent within the span of the chat completion
+    let requests = server.received_requests().await.unwrap();
+    let traceparent = requests[0].headers.get("traceparent").unwrap();
+    assert_eq!(
+        traceparent.to_str().unwrap(),
+        format!(
+            "00-{}-{}-01",
+            span.span_context.trace_id(),
+            span.span_context.span_id()
+        )
+    );
+}
@@ -0,0 +1,205 @@
+use ai::{ChatCompletionParameter, Message, ProviderPreferences, Role, ToolChoice, make_choice};
+
+/// Creates a parameter with a single user message for testing.
+fn create_parameter() -> ChatCompletionParameter<'static> {
+    let message = Message {
+        role: Role::User,
+        content: "Hello".into(),
+        tool_call_id: String::new(),
+        tool_calls: vec![],
+        refusal: None,
+        reasoning: None,
+    };
+
+    ChatCompletionParameter::new("openai/gpt-4.1".to_string(), vec![message])
+}
+
+#[test]
+fn test_temperature_range() {
+    let mut parameter = create_parameter();
+
+    assert!(parameter.set_temperature(0.0).is_ok());
+    assert!(parameter.set_temperature(2.0).is_ok());
+    assert!(parameter.set_temperature(-0.1).is_err());
+    assert!(parameter.set_temperature(2.1).is_err());
+    assert!(parameter.set_temperature(f64::NAN).is_err());
+
+    assert!(create_parameter().with_temperature(0.7).is_ok());
+    assert!(create_parameter().with_temperature(3.0).is_err());
+}
+
+#[test]
+fn test_top_p_range() {
+    let mut parameter = create_parameter();
+
+    assert!(parameter.set_top_p(1.0).is_ok());
+    assert!(parameter.set_top_p(0.1).is_ok());
+    assert!(parameter.set_top_p(0.0).is_err());
+    assert!(parameter.set_top_p(1.1).is_err());
+    assert!(parameter.set_top_p(f64::NAN).is_err());
+
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["top_p"], 0.1);
+    assert!(json.get("temperature").is_none());
+}
+
+#[test]
+fn test_max_tokens_serialization() {
+    let parameter = create_parameter().with_max_tokens(256);
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["max_tokens"], 256);
+
+    let json = serde_json::to_value(create_parameter().to_request()).unwrap();
+    assert!(json.get("max_tokens").is_none());
+}
+
+#[test]
+fn test_penalties() {
+    let mut parameter = create_parameter();
+
+    assert!(parameter.set_presence_penalty(-2.0).is_ok());
+    assert!(parameter.set_presence_penalty(2.5).is_err());
+    assert!(parameter.set_frequency_penalty(2.0).is_ok());
+    assert!(parameter.set_frequency_penalty(-2.5).is_err());
+
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["presence_penalty"], -2.0);
+    assert_eq!(json["frequency_penalty"], 2.0);
+
+    let parameter = create_parameter().with_presence_penalty(0.5).unwrap();
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["presence_penalty"], 0.5);
+    assert!(json.get("frequency_penalty").is_none());
+
+    assert!(create_parameter().with_frequency_penalty(3.0).is_err());
+}
+
+#[test]
+fn test_seed_serialization() {
+    let parameter = create_parameter().with_seed(42);
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["seed"], 42);
+}
+
+#[test]
+fn test_n_with_required_tool_choice() {
+    let mut parameter = create_parameter();
+    assert!(parameter.set_n(0).is_err());
+    assert!(parameter.set_n(3).is_ok());
+    assert!(parameter.set_tool_choice(ToolChoice::Required).is_err());
+    assert!(parameter.set_tool_choice(ToolChoice::Auto).is_ok());
+
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["n"], 3);
+
+    let mut parameter = create_parameter();
+    assert!(parameter.set_tool_choice(ToolChoice::Required).is_ok());
+    assert!(parameter.set_n(1).is_ok());
+    assert!(parameter.set_n(2).is_err());
+}
+
+#[test]
+fn test_top_logprobs() {
+    let mut parameter = create_parameter();
+    assert!(parameter.set_top_logprobs(21).is_err());
+    assert!(parameter.set_top_logprobs(5).is_ok());
+
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["logprobs"], true);
+    assert_eq!(json["top_logprobs"], 5);
+}
+
+#[test]
+fn test_fluent_builder() -> ai::Result<()> {
+    let parameter = create_parameter()
+        .with_temperature(0.2)?
+        .with_max_tokens(1024)
+        .with_seed(7)
+        .with_tool_choice(ToolChoice::Auto)?;
+
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["temperature"], 0.2);
+    assert_eq!(json["max_tokens"], 1024);
+    assert_eq!(json["seed"], 7);
+    assert_eq!(json["tool_choice"], "auto");
+
+    Ok(())
+}
+
+#[test]
+fn test_provider_preferences_serialization() {
+    let parameter = create_parameter();
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert!(json.get("provider").is_none());
+
+    let parameter = create_parameter().with_provider_preferences(ProviderPreferences {
+        order: vec!["OpenAI".to_string()],
+        allow_fallbacks: false,
+        ..Default::default()
+    });
+
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(
+        json["provider"],
+        serde_json::json!({
+            "order": ["OpenAI"],
+            "allow_fallbacks": false,
+            "require_parameters": false
+        })
+    );
+}
+
+#[test]
+fn test_messages() {
+    let mut parameter = create_parameter().with_message(Message::system("Be brief."));
+    assert_eq!(parameter.messages().len(), 2);
+    assert_eq!(parameter.messages()[1].role, Role::System);
+
+    parameter.set_messages(vec![Message::user("Hi")]);
+    assert_eq!(parameter.messages().len(), 1);
+
+    let json = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(json["messages"][0]["role"], "user");
+    assert_eq!(json["messages"][0]["content"], "Hi");
+}
+
+#[test]
+fn test_prepend_few_shot() {
+    let examples = [
+        (Message::user("2+2"), make_choice("4").message),
+        (Message::user("3+3"), make_choice("6").message),
+    ];
+    let contents = |parameter: &ChatCompletionParameter| -> Vec<String> {
+        parameter
+            .messages()
+            .iter()
+            .map(|m| m.content.to_text())
+            .collect()
+    };
+
+    let mut parameter = ChatCompletionParameter::new("openai/gpt-4.1".to_string(), vec![]);
+    parameter.prepend_few_shot(&examples);
+    assert_eq!(contents(&parameter), ["2+2", "4", "3+3", "6"]);
+
+    let mut parameter = create_parameter();
+    parameter.prepend_few_shot(&examples);
+    assert_eq!(contents(&parameter), ["2+2", "4", "3+3", "6", "Hello"]);
+
+    let mut parameter = ChatCompletionParameter::new(
+        "openai/gpt-4.1".to_string(),
+        vec![
+            Message::system("Calculate."),
+            Message::system("Be brief."),
+            Message::user("4+4"),
+        ],
+    );
+    parameter.prepend_few_shot(&examples);
+    assert_eq!(
+        contents(&parameter),
+        ["Calculate.", "Be brief.", "2+2", "4", "3+3", "6", "4+4"]
+    );
+    assert_eq!(parameter.messages()[3].role, Role::Assistant);
+
+    parameter.prepend_few_shot(&[]);
+    assert_eq!(parameter.messages().len(), 7);
+}
@@ -0,0 +1,27 @@
+use ai::Client;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_requests_are_routed_through_proxy() {
+    let proxy = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v1/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data": []}"#))
+        .mount(&proxy)
+        .await;
+
+    // the API host does not exist, so the request only succeeds via the proxy
+    let api_url = "http://api.invalid/api/v1/".parse().unwrap();
+    let client = Client::new("test-key".to_string(), api_url)
+        .unwrap()
+        .with_proxy(proxy.uri().parse().unwrap())
+        .unwrap();
+
+    let models = client.get_models().await.unwrap();
+    assert!(models.get_models().is_empty());
+
+    let requests = proxy.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use std::time::Duration;
+
+use ai::{Client, Error, RetryConfig};
+use common::{chat_response, create_parameter};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Creates a retry configuration with short delays for testing.
+fn create_retry_config(max_attempts: u32) -> RetryConfig {
+    RetryConfig {
+        max_attempts,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+        backoff_factor: 2.0,
+        jitter: false,
+    }
+}
+
+/// Creates a client for the given mock server.
+fn create_client(server: &MockServer, max_attempts: u32) -> Client {
+    common::create_client(server).with_retry_config(create_retry_config(max_attempts))
+}
+
+#[tokio::test]
+async fn test_retry_until_success() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data": []}"#))
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server, 3);
+    let models = client.get_models().await.unwrap();
+    assert!(models.get_models().is_empty());
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 3);
+}
+
+#[tokio::test]
+async fn test_retry_exhausted() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server, 4);
+    let result = client.get_models().await;
+    assert!(matches!(result, Err(Error::HTTPErrorWithStatusCode(_))));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 4);
+}
+
+#[tokio::test]
+async fn test_no_retry_on_permanent_error() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(401))
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server, 3);
+    assert!(client.get_models().await.is_err());
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_retry_after() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "7"))
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server, 1);
+    let parameter = create_parameter();
+    let result = client.chat_completion(&parameter).await;
+
+    match result {
+        Err(Error::RateLimit { retry_after }) => {
+            assert_eq!(retry_after, Some(Duration::from_secs(7)));
+        }
+        other => panic!("Expected rate limit error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_retry_respects_retry_after() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response())
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    let client = common::create_client(&server).with_retry_config(RetryConfig {
+        max_delay: Duration::from_secs(5),
+        ..create_retry_config(2)
+    });
+
+    let parameter = create_parameter();
+    let start = std::time::Instant::now();
+    client.chat_completion(&parameter).await.unwrap();
+
+    // the Retry-After value is used instead of the short exponential backoff
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
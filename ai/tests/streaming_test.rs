@@ -0,0 +1,222 @@
+mod common;
+
+use std::time::Duration;
+
+use ai::streaming::DeltaAccumulator;
+use ai::{Client, Error, FinishReason, RetryConfig};
+use common::{create_client, create_parameter};
+use futures::StreamExt;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Mounts a streaming chat completion response with the given body.
+async fn mount_stream(server: &MockServer, body: &str) {
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(serde_json::json!({"stream": true})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Type", "text/event-stream")
+                .set_body_string(body),
+        )
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_chat_completion_stream() {
+    let server = MockServer::start().await;
+    mount_stream(
+        &server,
+        ": OPENROUTER PROCESSING\n\n\
+         data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"role\": \"assistant\", \"content\": \"Hello\"}}]}\n\n\
+         event: ping\ndata: {}\n\n\
+         data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \" World\"}, \"finish_reason\": \"stop\"}]}\n\n\
+         data: [DONE]\n\n",
+    )
+    .await;
+
+    let client = create_client(&server);
+    let parameter = create_parameter();
+    let mut stream = client.chat_completion_stream(&parameter).await.unwrap();
+
+    let mut accumulator = DeltaAccumulator::new();
+    let mut finish_reason = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.unwrap();
+        for choice in chunk.choices.iter() {
+            accumulator.push(&choice.delta).unwrap();
+            if choice.finish_reason.is_some() {
+                finish_reason = choice.finish_reason.clone();
+            }
+        }
+    }
+
+    let message = accumulator.finish().unwrap();
+    assert_eq!(message.content.to_text(), "Hello World");
+    assert_eq!(finish_reason, Some(FinishReason::Stop));
+}
+
+#[tokio::test]
+async fn test_chat_completion_stream_error_event() {
+    let server = MockServer::start().await;
+    mount_stream(
+        &server,
+        "data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \"Hel\"}}]}\n\n\
+         event: error\ndata: {\"message\": \"upstream failed\"}\n\n",
+    )
+    .await;
+
+    let client = create_client(&server);
+    let parameter = create_parameter();
+    let stream = client.chat_completion_stream(&parameter).await.unwrap();
+    let chunks: Vec<_> = stream.collect().await;
+
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks[0].is_ok());
+    match &chunks[1] {
+        Err(Error::StreamError(body)) => assert!(body.contains("upstream failed")),
+        other => panic!("Expected stream error, got {:?}", other),
+    }
+}
+
+/// Creates a client for the given mock server reconnecting interrupted streams.
+fn create_reconnecting_client(server: &MockServer) -> Client {
+    create_client(server).with_retry_config(RetryConfig {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+        backoff_factor: 2.0,
+        jitter: false,
+    })
+}
+
+#[tokio::test]
+async fn test_chat_completion_stream_reconnect() {
+    let server = MockServer::start().await;
+
+    // the first response ends within the second event, which is sent again after reconnecting
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Type", "text/event-stream")
+                .set_body_string(
+                    "id: 1\n\
+                     data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \"Hello\"}}]}\n\n\
+                     id: 2\ndata: {\"id\": \"gen-1\", \"choi",
+                ),
+        )
+        .up_to_n_times(1)
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Last-Event-ID", "1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Type", "text/event-stream")
+                .set_body_string(
+                    "id: 2\n\
+                     data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \" World\"}}]}\n\n\
+                     data: [DONE]\n\n",
+                ),
+        )
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    let client = create_reconnecting_client(&server);
+    let parameter = create_parameter();
+    let stream = client.chat_completion_stream(&parameter).await.unwrap();
+
+    let (contents, errors) = collect_stream(stream).await;
+    assert_eq!(contents, ["Hello", " World"]);
+    assert!(errors.is_empty());
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+/// Returns the contents of the chunks and the errors of the given stream.
+async fn collect_stream(stream: ai::streaming::ChatCompletionStream) -> (Vec<String>, Vec<Error>) {
+    let mut contents = Vec::new();
+    let mut errors = Vec::new();
+
+    let items: Vec<_> = stream.collect().await;
+    for item in items {
+        match item {
+            Ok(chunk) => contents.push(chunk.choices[0].delta.content.clone().unwrap()),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    (contents, errors)
+}
+
+#[tokio::test]
+async fn test_chat_completion_stream_interrupted() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Type", "text/event-stream")
+                .set_body_string(
+                    "id: 1\n\
+                     data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \"Hello\"}}]}\n\n",
+                ),
+        )
+        .up_to_n_times(1)
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    // the resumed streams end again without any new events
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Last-Event-ID", "1"))
+        .respond_with(ResponseTemplate::new(200).insert_header("Content-Type", "text/event-stream"))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    let client = create_reconnecting_client(&server);
+    let parameter = create_parameter();
+    let stream = client.chat_completion_stream(&parameter).await.unwrap();
+
+    let (contents, errors) = collect_stream(stream).await;
+    assert_eq!(contents, ["Hello"]);
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        &errors[0],
+        Error::StreamInterrupted { last_event_id: Some(id) } if id == "1"
+    ));
+
+    // the first request and one reconnect per remaining attempt
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_chat_completion_stream_without_event_ids() {
+    let server = MockServer::start().await;
+
+    // the stream ends without [DONE] and the trailing event is not terminated by an empty line
+    mount_stream(
+        &server,
+        "data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \"Hello\"}}]}\n\n\
+         data: {\"id\": \"gen-1\", \"choices\": [{\"index\": 0, \"delta\": {\"content\": \" World\"}}]}",
+    )
+    .await;
+
+    let client = create_reconnecting_client(&server);
+    let parameter = create_parameter();
+    let stream = client.chat_completion_stream(&parameter).await.unwrap();
+
+    // without event ids the stream is never sent again
+    let (contents, errors) = collect_stream(stream).await;
+    assert_eq!(contents, ["Hello", " World"]);
+    assert!(errors.is_empty());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
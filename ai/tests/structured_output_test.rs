@@ -1,8 +1,14 @@
+mod common;
+
 use std::collections::BTreeSet;
 
-use ai::{JsonSchemaDescription, json_types::ResponseFormat};
+use ai::json_types::{JsonFunctionCall, ResponseFormat};
+use ai::{Error, JsonSchemaDescription, JsonToolCall, make_choice};
+use common::{create_client, create_parameter};
 use schemars::{JsonSchema, generate::SchemaSettings, transform::AddNullable};
 use serde::{Deserialize, Serialize};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[derive(JsonSchema, Serialize, Deserialize, Debug, PartialEq)]
 #[schemars(deny_unknown_fields)]
@@ -85,3 +91,186 @@ fn test_schema() {
         "Parsed JSON schema does not match the reference"
     );
 }
+
+#[derive(JsonSchema, Serialize, Deserialize, Debug)]
+#[schemars(deny_unknown_fields)]
+struct Forecast {
+    /// The forecast for each day
+    pub days: Vec<Day>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize, Debug)]
+#[schemars(deny_unknown_fields)]
+struct Day {
+    /// The maximum temperature in Celsius
+    pub max_temperature: f32,
+}
+
+/// Creates a strict JSON schema description for the given type.
+fn create_json_schema<T: JsonSchema>(name: &str) -> JsonSchemaDescription {
+    let settings = SchemaSettings::default().with_transform(AddNullable::default());
+    let generator = settings.into_generator();
+
+    JsonSchemaDescription {
+        name: name.to_string(),
+        strict: true,
+        schema: generator.into_root_schema_for::<T>(),
+    }
+}
+
+#[test]
+fn test_validate_for_structured_output() {
+    let json_schema = create_json_schema::<Forecast>("forecast");
+
+    let mut parameter = create_parameter();
+    assert!(parameter.validate_for_structured_output().is_ok());
+
+    parameter.set_response_format(ResponseFormat {
+        schema_type: "json_schema",
+        json_schema: Some(&json_schema),
+    });
+    assert!(parameter.validate_for_structured_output().is_ok());
+}
+
+#[test]
+fn test_validate_for_structured_output_missing_required() {
+    let json_schema = create_json_schema::<Weather>("weather");
+
+    let mut parameter = create_parameter();
+    parameter.set_response_format(ResponseFormat {
+        schema_type: "json_schema",
+        json_schema: Some(&json_schema),
+    });
+
+    match parameter.validate_for_structured_output() {
+        Err(Error::InternalError(message)) => {
+            assert!(message.contains("'#/humidity' is not required"));
+            assert!(!message.contains("additionalProperties"));
+        }
+        other => panic!("Expected validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_json_object_response_format() {
+    let response_format = ResponseFormat::json_object();
+    assert!(response_format.is_json_object());
+    assert_eq!(
+        serde_json::to_value(&response_format).unwrap(),
+        serde_json::json!({"type": "json_object"})
+    );
+
+    let parameter = create_parameter().with_response_format(ResponseFormat::json_object());
+    assert!(parameter.validate_for_structured_output().is_ok());
+
+    let request = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(
+        request["response_format"],
+        serde_json::json!({"type": "json_object"})
+    );
+
+    let json_schema = create_json_schema::<Forecast>("forecast");
+    let response_format = ResponseFormat {
+        schema_type: "json_schema",
+        json_schema: Some(&json_schema),
+    };
+    assert!(!response_format.is_json_object());
+}
+
+/// Starts a mock server responding to chat completions with the given content.
+async fn start_content_server(content: &str) -> MockServer {
+    let server = MockServer::start().await;
+
+    let mut response: serde_json::Value =
+        serde_json::from_str(include_str!("../test_data/weather_tool_response.json")).unwrap();
+    response["choices"][0]["message"]["content"] = content.into();
+    response["choices"][0]["message"]["tool_calls"] = serde_json::json!([]);
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+/// Sends a chat completion request with the forecast schema to a server returning the content.
+async fn chat_completion_with_schema(content: &str, validate: bool) -> ai::Result<()> {
+    let server = start_content_server(content).await;
+    let client = create_client(&server);
+
+    let json_schema = create_json_schema::<Forecast>("forecast");
+    let parameter = create_parameter()
+        .with_response_format(ResponseFormat {
+            schema_type: "json_schema",
+            json_schema: Some(&json_schema),
+        })
+        .with_schema_validation(validate);
+
+    client.chat_completion(&parameter).await.map(|_| ())
+}
+
+#[tokio::test]
+async fn test_schema_validation() {
+    let valid = r#"{"days": [{"max_temperature": 21.5}]}"#;
+    assert!(chat_completion_with_schema(valid, true).await.is_ok());
+
+    let invalid = r#"{"days": [{"max_temperature": "warm"}]}"#;
+    assert!(chat_completion_with_schema(invalid, false).await.is_ok());
+    match chat_completion_with_schema(invalid, true).await {
+        Err(Error::SchemaValidationFailed { content, errors }) => {
+            assert_eq!(content, invalid);
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].starts_with("/days/0/max_temperature"));
+        }
+        other => panic!("Expected schema validation error, got {:?}", other),
+    }
+
+    match chat_completion_with_schema("not json", true).await {
+        Err(Error::SchemaValidationFailed { errors, .. }) => {
+            assert!(errors[0].starts_with("Invalid JSON"));
+        }
+        other => panic!("Expected schema validation error, got {:?}", other),
+    }
+}
+
+/// Validates the given choices against the forecast schema.
+fn validate_forecast_choices(choices: &[ai::Choice]) -> ai::Result<()> {
+    let json_schema = create_json_schema::<Forecast>("forecast");
+    create_parameter()
+        .with_response_format(ResponseFormat {
+            schema_type: "json_schema",
+            json_schema: Some(&json_schema),
+        })
+        .with_schema_validation(true)
+        .validate_choices(choices)
+}
+
+#[test]
+fn test_schema_validation_skips_tool_calls() {
+    let mut tool_call = make_choice("");
+    tool_call.message.tool_calls.push(JsonToolCall {
+        index: 0,
+        id: "call_0".to_string(),
+        r#type: "function".to_string(),
+        function_call: JsonFunctionCall {
+            name: "get_forecast".to_string(),
+            arguments: r#"{"city": "Berlin"}"#.to_string(),
+        },
+    });
+    assert!(validate_forecast_choices(&[tool_call]).is_ok());
+
+    assert!(matches!(
+        validate_forecast_choices(&[make_choice("")]),
+        Err(Error::SchemaValidationFailed { .. })
+    ));
+}
+
+#[test]
+fn test_schema_validation_skips_refusals() {
+    let mut refusal = make_choice("");
+    refusal.message.refusal = Some("I'm sorry, I can't help with that request.".to_string());
+    assert!(refusal.is_refused());
+    assert!(validate_forecast_choices(&[refusal]).is_ok());
+}
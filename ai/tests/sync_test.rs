@@ -0,0 +1,43 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use ai::sync::SyncClient;
+use common::{api_url, chat_response, create_parameter};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[test]
+fn test_sync_client() {
+    // the mock server runs on its own thread, so the runtime is only needed for starting it
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(chat_response())
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data": []}"#))
+            .mount(&server)
+            .await;
+
+        server
+    });
+
+    let client = SyncClient::new("test-key".to_string(), api_url(&server)).unwrap();
+
+    let parameter = create_parameter();
+    let choices = client.chat_completion(&parameter).unwrap();
+    assert_eq!(choices.len(), 1);
+
+    assert!(client.get_models().unwrap().get_models().is_empty());
+}
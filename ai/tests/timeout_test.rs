@@ -0,0 +1,71 @@
+mod common;
+
+use std::time::Duration;
+
+use ai::{ConnectionConfig, Error};
+use common::{chat_response, create_client, create_parameter};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer};
+
+/// Starts a mock server that answers chat completion requests after the given delay.
+async fn start_delayed_server(delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response().set_delay(delay))
+        .mount(&server)
+        .await;
+
+    server
+}
+
+#[tokio::test]
+async fn test_request_timeout_override() {
+    let server = start_delayed_server(Duration::from_millis(500)).await;
+    let client = create_client(&server);
+
+    let parameter = create_parameter().with_timeout(Duration::from_millis(50));
+    let result = client.chat_completion(&parameter).await;
+
+    match result {
+        Err(Error::HTTPError(e)) => assert!(e.is_timeout()),
+        other => panic!("Expected timeout error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_default_timeout() {
+    let server = start_delayed_server(Duration::from_millis(500)).await;
+    let client = create_client(&server)
+        .with_default_timeout(Duration::from_millis(50))
+        .unwrap();
+
+    let parameter = create_parameter();
+    assert!(client.chat_completion(&parameter).await.is_err());
+
+    // the per-request timeout takes precedence over the default timeout
+    let parameter = parameter.with_timeout(Duration::from_secs(5));
+    assert!(client.chat_completion(&parameter).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_connection_config_keeps_timeout() {
+    let server = start_delayed_server(Duration::from_millis(500)).await;
+    let client = create_client(&server)
+        .with_default_timeout(Duration::from_millis(50))
+        .unwrap()
+        .with_connection_config(ConnectionConfig {
+            max_idle_per_host: 1,
+            idle_timeout: Duration::from_secs(10),
+            tcp_keepalive: Some(Duration::from_secs(30)),
+            tcp_nodelay: true,
+        })
+        .unwrap();
+
+    let parameter = create_parameter();
+    assert!(client.chat_completion(&parameter).await.is_err());
+
+    let parameter = parameter.with_timeout(Duration::from_secs(5));
+    assert!(client.chat_completion(&parameter).await.is_ok());
+}
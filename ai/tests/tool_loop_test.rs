@@ -0,0 +1,161 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ai::{
+    ChatCompletionParameter, ChatCompletionResponse, Error, Message, MockClient, Role, Tool,
+    ToolRegistry, make_choice, run_tool_loop,
+};
+use common::{chat_response, create_client, mount_chat_response};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct WeatherParameter {
+    /// City and country e.g. Bogotá, Colombia
+    pub location: String,
+}
+
+/// The final response of the model after receiving the tool results.
+const FINAL_RESPONSE: &str = r#"
+{
+    "id": "gen-2",
+    "created": 1747684760,
+    "choices": [
+        {
+            "finish_reason": "stop",
+            "native_finish_reason": "stop",
+            "index": 0,
+            "message": { "role": "assistant", "content": "It is sunny in London." }
+        }
+    ],
+    "usage": { "prompt_tokens": 90, "completion_tokens": 7, "total_tokens": 97 }
+}
+"#;
+
+/// Creates a registry with the weather tool.
+fn create_registry() -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry.register(
+        Tool::<WeatherParameter>::new(
+            "get_weather".to_string(),
+            "Get current temperature for a given location.".to_string(),
+        ),
+        |parameter| Box::pin(async move { Ok(format!("Sunny in {}", parameter.location)) }),
+    );
+
+    registry
+}
+
+/// Creates a parameter with a single user message.
+fn create_parameter() -> ChatCompletionParameter<'static> {
+    let message = Message {
+        role: Role::User,
+        content: "What is the weather like in London?".into(),
+        tool_call_id: String::new(),
+        tool_calls: vec![],
+        refusal: None,
+        reasoning: None,
+    };
+
+    ChatCompletionParameter::new("openai/gpt-4.1".to_string(), vec![message])
+}
+
+#[tokio::test]
+async fn test_run_tool_loop() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(chat_response())
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(FINAL_RESPONSE))
+        .with_priority(2)
+        .mount(&server)
+        .await;
+
+    let client = create_client(&server);
+    let registry = create_registry();
+    let mut parameter = create_parameter();
+    registry.apply_to(&mut parameter).unwrap();
+
+    let choices = client
+        .run_tool_loop(&mut parameter, &registry, 5)
+        .await
+        .unwrap();
+    assert_eq!(
+        choices[0].message.content.to_text(),
+        "It is sunny in London."
+    );
+
+    // the second request must contain the assistant message and the tool result
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(messages[2]["role"], "tool");
+    assert_eq!(messages[2]["tool_call_id"], "call_L8RNjCRpMAxGkCAy5ovJxkw9");
+    assert_eq!(messages[2]["content"], "Sunny in London, United Kingdom");
+}
+
+#[tokio::test]
+async fn test_run_tool_loop_max_iterations() {
+    let server = MockServer::start().await;
+
+    mount_chat_response(&server).await;
+
+    let client = create_client(&server);
+    let registry = create_registry();
+    let mut parameter = create_parameter();
+
+    let result = client.run_tool_loop(&mut parameter, &registry, 2).await;
+    assert!(matches!(result, Err(Error::InternalError(_))));
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_run_tool_loop_with_mock_client() {
+    let num_requests = AtomicUsize::new(0);
+    let client = MockClient::new().on_request(move |_| {
+        if num_requests.fetch_add(1, Ordering::SeqCst) == 0 {
+            let response: ChatCompletionResponse =
+                serde_json::from_str(include_str!("../test_data/weather_tool_response.json"))
+                    .unwrap();
+            Ok(response.choices)
+        } else {
+            Ok(vec![make_choice("It is sunny in London.")])
+        }
+    });
+
+    let registry = create_registry();
+    let mut parameter = create_parameter();
+
+    let choices = run_tool_loop(&client, &mut parameter, &registry, 5)
+        .await
+        .unwrap();
+    assert_eq!(
+        choices[0].message.content.to_text(),
+        "It is sunny in London."
+    );
+
+    let requests = client.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(requests[1]["messages"][2]["role"], "tool");
+    assert_eq!(
+        requests[1]["messages"][2]["content"],
+        "Sunny in London, United Kingdom"
+    );
+}
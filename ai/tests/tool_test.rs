@@ -1,6 +1,16 @@
+mod common;
+
 use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
-use ai::{ChatCompletionResponse, Tool};
+use ai::json_types::{JsonFunctionCall, JsonToolCall};
+use ai::{
+    ChatCompletionResponse, Error, FinishReason, NullableStrategy, Role, Tool, ToolRegistry,
+    dispatch_all_tool_calls, tools,
+};
+use common::create_parameter;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -95,10 +105,10 @@ fn test_tool_response_decoding() {
     assert_eq!(weather_tool_response.choices.len(), 1);
 
     let choice = &weather_tool_response.choices[0];
-    assert_eq!(choice.finish_reason, "tool_calls");
+    assert_eq!(choice.finish_reason, FinishReason::ToolCalls);
 
     let message = &choice.message;
-    assert_eq!(message.role, "assistant");
+    assert_eq!(message.role, Role::Assistant);
 
     assert_eq!(message.tool_calls.len(), 1);
 
@@ -112,3 +122,352 @@ fn test_tool_response_decoding() {
         "{\"location\":\"London, United Kingdom\"}"
     );
 }
+
+/// Creates a tool call for the function with the given name and arguments.
+fn create_tool_call(index: i64, name: &str, arguments: &str) -> JsonToolCall {
+    JsonToolCall {
+        index,
+        id: format!("call_{}", index),
+        r#type: "function".to_string(),
+        function_call: JsonFunctionCall {
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_dispatch_all_tool_calls() {
+    let mut registry = ToolRegistry::new();
+    registry.register_handler("echo".to_string(), |arguments| {
+        Box::pin(async move { Ok(arguments) })
+    });
+
+    let calls = vec![
+        create_tool_call(0, "echo", "{\"a\":1}"),
+        create_tool_call(1, "unknown", "{}"),
+        create_tool_call(2, "echo", "{\"b\":2}"),
+    ];
+
+    let results = dispatch_all_tool_calls(&calls, &registry).await;
+    assert_eq!(results.len(), 3);
+
+    assert_eq!(results[0].0, "call_0");
+    assert_eq!(results[0].1.as_ref().unwrap(), "{\"a\":1}");
+
+    assert_eq!(results[1].0, "call_1");
+    assert!(matches!(results[1].1, Err(Error::ToolNotFound(_))));
+
+    assert_eq!(results[2].0, "call_2");
+    assert_eq!(results[2].1.as_ref().unwrap(), "{\"b\":2}");
+
+    assert!(dispatch_all_tool_calls(&[], &registry).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_tool_registry_typed_dispatch() {
+    let mut registry = ToolRegistry::new();
+    registry.register(
+        Tool::<WeatherParameter>::new(
+            "get_weather".to_string(),
+            "Get current temperature for a given location.".to_string(),
+        ),
+        |parameter| Box::pin(async move { Ok(format!("Sunny in {}", parameter.location)) }),
+    );
+
+    let call = create_tool_call(0, "get_weather", "{\"location\":\"Paris, France\"}");
+    assert_eq!(
+        registry.dispatch(&call).await.unwrap(),
+        "Sunny in Paris, France"
+    );
+
+    let call = create_tool_call(1, "get_weather", "{\"city\":\"Paris\"}");
+    assert!(matches!(
+        registry.dispatch(&call).await,
+        Err(Error::Deserialization(_))
+    ));
+
+    let mut parameter = create_parameter();
+    registry.apply_to(&mut parameter).unwrap();
+
+    let request = serde_json::to_value(parameter.to_request()).unwrap();
+    let tools = request["tools"].as_array().unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0]["function"]["name"], "get_weather");
+}
+
+#[test]
+fn test_add_duplicate_tool() {
+    let create_tool = || {
+        Tool::<WeatherParameter>::new(
+            "get_weather".to_string(),
+            "Get current temperature for a given location.".to_string(),
+        )
+    };
+
+    let mut parameter = create_parameter().with_tool(create_tool()).unwrap();
+
+    let result = parameter.add_tool(create_tool());
+    assert!(matches!(result, Err(Error::DuplicateTool(name)) if name == "get_weather"));
+
+    let request = serde_json::to_value(parameter.to_request()).unwrap();
+    assert_eq!(request["tools"].as_array().unwrap().len(), 1);
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct WeatherOutput {
+    /// The temperature in degrees Celsius.
+    pub temperature: f64,
+}
+
+#[tokio::test]
+async fn test_tool_output_schema() {
+    let tool = Tool::<WeatherParameter>::new("get_weather".to_string(), String::new()).into_json();
+    assert!(tool.function.output_schema.is_none());
+    let tool_json = serde_json::to_value(&tool).unwrap();
+    assert!(tool_json["function"].get("output_schema").is_none());
+
+    let tool =
+        Tool::<WeatherParameter, WeatherOutput>::new("get_weather".to_string(), String::new())
+            .into_json();
+    let tool_json = serde_json::to_value(&tool).unwrap();
+    assert_eq!(
+        tool_json["function"]["output_schema"]["properties"]["temperature"]["type"],
+        "number"
+    );
+
+    let mut registry = ToolRegistry::new();
+    registry.register(
+        Tool::<WeatherParameter, WeatherOutput>::new("get_weather".to_string(), String::new()),
+        |parameter| {
+            Box::pin(async move {
+                if parameter.location.is_empty() {
+                    Ok("unknown".to_string())
+                } else {
+                    Ok("{\"temperature\":21.5}".to_string())
+                }
+            })
+        },
+    );
+
+    let call = create_tool_call(0, "get_weather", "{\"location\":\"Paris, France\"}");
+    assert_eq!(
+        registry.dispatch(&call).await.unwrap(),
+        "{\"temperature\":21.5}"
+    );
+
+    let call = create_tool_call(1, "get_weather", "{\"location\":\"\"}");
+    assert!(matches!(
+        registry.dispatch(&call).await,
+        Err(Error::Deserialization(_))
+    ));
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct ForecastParameter {
+    /// City and country e.g. Bogotá, Colombia
+    pub location: String,
+
+    /// The optional unit of the temperature
+    pub unit: Option<String>,
+}
+
+/// Returns the JSON parameters of the forecast tool with the given nullable strategy.
+fn forecast_parameters(strategy: NullableStrategy) -> serde_json::Value {
+    let tool = Tool::<ForecastParameter>::new("get_forecast".to_string(), String::new())
+        .with_nullable_strategy(strategy)
+        .into_json();
+
+    serde_json::to_value(&tool).unwrap()["function"]["parameters"].clone()
+}
+
+#[test]
+fn test_nullable_strategy() {
+    let parameters = forecast_parameters(NullableStrategy::AddNull);
+    assert_eq!(
+        parameters["properties"]["unit"]["type"],
+        serde_json::json!(["string", "null"])
+    );
+    assert_eq!(
+        parameters["required"],
+        serde_json::json!(["location", "unit"])
+    );
+
+    let parameters = forecast_parameters(NullableStrategy::AnyOf);
+    assert_eq!(
+        parameters["properties"]["unit"],
+        serde_json::json!({
+            "description": "The optional unit of the temperature",
+            "anyOf": [{"type": "string"}, {"type": "null"}]
+        })
+    );
+    assert_eq!(
+        parameters["required"],
+        serde_json::json!(["location", "unit"])
+    );
+
+    let parameters = forecast_parameters(NullableStrategy::Omit);
+    assert_eq!(parameters["properties"]["unit"]["type"], "string");
+    assert_eq!(parameters["required"], serde_json::json!(["location"]));
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct Coordinates {
+    /// The latitude of the location.
+    pub latitude: f64,
+
+    /// The longitude of the location.
+    pub longitude: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RouteParameter {
+    /// The start of the route.
+    pub start: Coordinates,
+
+    /// The optional stops of the route.
+    pub stops: Vec<Coordinates>,
+}
+
+#[test]
+fn test_tool_schema_inline_refs() {
+    let tool = Tool::<RouteParameter>::new("get_route".to_string(), String::new()).into_json();
+    let tool_json = serde_json::to_value(&tool).unwrap();
+    assert!(!tool_json.to_string().contains("$ref"));
+
+    let parameters = &tool_json["function"]["parameters"];
+    assert!(parameters.get("$defs").is_none());
+    assert_eq!(
+        parameters["properties"]["start"]["description"],
+        "The start of the route."
+    );
+    assert_eq!(
+        parameters["properties"]["start"]["properties"]["latitude"]["type"],
+        "number"
+    );
+    assert_eq!(
+        parameters["properties"]["stops"]["items"]["required"],
+        serde_json::json!(["latitude", "longitude"])
+    );
+}
+
+#[tokio::test]
+async fn test_execute_parallel() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+    let mut registry = ToolRegistry::new();
+    {
+        let in_flight = in_flight.clone();
+        let max_in_flight = max_in_flight.clone();
+        registry.register_handler("sleep".to_string(), move |arguments| {
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            Box::pin(async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(arguments)
+            })
+        });
+    }
+
+    let calls = vec![
+        create_tool_call(0, "sleep", "{\"a\":1}"),
+        create_tool_call(1, "unknown", "{}"),
+        create_tool_call(2, "sleep", "{\"b\":2}"),
+        create_tool_call(3, "sleep", "{\"c\":3}"),
+    ];
+
+    let results = tools::execute_parallel(&calls, &registry).await;
+    let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, ["call_0", "call_1", "call_2", "call_3"]);
+
+    assert_eq!(results[0].1.as_ref().unwrap(), "{\"a\":1}");
+    assert!(matches!(results[1].1, Err(Error::ToolNotFound(_))));
+    assert_eq!(results[3].1.as_ref().unwrap(), "{\"c\":3}");
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_execute_sequential() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let in_flight = Arc::new(AtomicUsize::new(0));
+
+    let mut registry = ToolRegistry::new();
+    {
+        let log = log.clone();
+        let in_flight = in_flight.clone();
+        registry.register_handler("record".to_string(), move |arguments| {
+            let log = log.clone();
+            let in_flight = in_flight.clone();
+            Box::pin(async move {
+                assert_eq!(in_flight.fetch_add(1, Ordering::SeqCst), 0);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                log.lock().unwrap().push(arguments.clone());
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(arguments)
+            })
+        });
+    }
+
+    let calls = vec![
+        create_tool_call(0, "record", "write"),
+        create_tool_call(1, "unknown", "{}"),
+        create_tool_call(2, "record", "read"),
+    ];
+
+    let results = tools::execute_sequential(&calls, &registry).await;
+    let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, ["call_0", "call_1", "call_2"]);
+
+    assert!(matches!(results[1].1, Err(Error::ToolNotFound(_))));
+    assert_eq!(results[2].1.as_ref().unwrap(), "read");
+    assert_eq!(*log.lock().unwrap(), ["write", "read"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tool_timeout() {
+    /// Returns a handler sleeping for the given duration before answering.
+    fn sleeping_handler(duration: Duration) -> impl Fn(WeatherParameter) -> ai::ToolFuture {
+        move |parameter| {
+            Box::pin(async move {
+                tokio::time::sleep(duration).await;
+                Ok(format!("Sunny in {}", parameter.location))
+            })
+        }
+    }
+
+    let mut registry = ToolRegistry::new().with_default_timeout(Duration::from_secs(30));
+    registry.register(
+        Tool::<WeatherParameter>::new("slow".to_string(), "Slow tool".to_string()),
+        sleeping_handler(Duration::from_secs(60)),
+    );
+    registry.register_with_timeout(
+        Tool::<WeatherParameter>::new("patient".to_string(), "Patient tool".to_string()),
+        sleeping_handler(Duration::from_secs(60)),
+        Duration::from_secs(120),
+    );
+
+    let arguments = "{\"location\":\"Paris\"}";
+    let slow_call = create_tool_call(0, "slow", arguments);
+    let (result, _) = tokio::join!(
+        registry.dispatch(&slow_call),
+        tokio::time::advance(Duration::from_secs(31))
+    );
+    match result {
+        Err(Error::ToolTimeout { tool_name, elapsed }) => {
+            assert_eq!(tool_name, "slow");
+            assert_eq!(elapsed, Duration::from_secs(30));
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    let patient_call = create_tool_call(1, "patient", arguments);
+    let (result, _) = tokio::join!(
+        registry.dispatch(&patient_call),
+        tokio::time::advance(Duration::from_secs(61))
+    );
+    assert_eq!(result.unwrap(), "Sunny in Paris");
+}
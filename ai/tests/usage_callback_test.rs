@@ -0,0 +1,32 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use ai::UsageEvent;
+use common::{create_client, create_parameter, mount_chat_response};
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn test_usage_callback() {
+    let server = MockServer::start().await;
+
+    mount_chat_response(&server).await;
+
+    let events: Arc<Mutex<Vec<UsageEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let callback_events = events.clone();
+
+    let client = create_client(&server).with_usage_callback(Arc::new(move |event| {
+        callback_events.lock().unwrap().push(event);
+    }));
+
+    let parameter = create_parameter();
+    client.chat_completion(&parameter).await.unwrap();
+    client.chat_completion(&parameter).await.unwrap();
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].usage.prompt_tokens, 64);
+    assert_eq!(events[0].usage.completion_tokens, 17);
+    assert!(!events[0].response_id.is_empty());
+    assert!(!events[0].model.is_empty());
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use ai::{Cassette, ChatCompletionParameter, Client, Message, Role, VcrMode};
+use common::api_url;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Creates a parameter with a single user message.
+fn create_parameter(model: &str, content: &str) -> ChatCompletionParameter<'static> {
+    let message = Message {
+        role: Role::User,
+        content: content.into(),
+        tool_call_id: String::new(),
+        tool_calls: vec![],
+        refusal: None,
+        reasoning: None,
+    };
+
+    ChatCompletionParameter::new(model.to_string(), vec![message])
+}
+
+#[tokio::test]
+async fn test_vcr_playback() {
+    let cassette =
+        Cassette::for_current_test(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cassettes"))
+            .unwrap();
+
+    let api_key = std::env::var("API_KEY").unwrap_or_else(|_| "test-key".to_string());
+    let client = Client::new(api_key, "https://openrouter.ai/api/v1/".parse().unwrap())
+        .unwrap()
+        .with_cassette(cassette);
+
+    let parameter = create_parameter("openai/gpt-3.5-turbo", "How are you?");
+    let choices = client.chat_completion(&parameter).await.unwrap();
+    assert_eq!(choices.len(), 1);
+}
+
+#[tokio::test]
+async fn test_vcr_record_and_playback() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data": []}"#))
+        .mount(&server)
+        .await;
+
+    let cassette_path = std::env::temp_dir().join(format!(
+        "rs_ai_test_vcr_record_and_playback_{}.json",
+        std::process::id()
+    ));
+    let api_url = api_url(&server);
+
+    // record the interaction, the cassette is saved when the client is dropped
+    {
+        let cassette = Cassette::new(&cassette_path, VcrMode::Record).unwrap();
+        let client = Client::new("secret-key".to_string(), api_url.clone())
+            .unwrap()
+            .with_cassette(cassette);
+
+        client.get_models().await.unwrap();
+    }
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    let content = std::fs::read_to_string(&cassette_path).unwrap();
+    assert!(!content.contains("secret-key"));
+
+    // play back the interaction without sending a request
+    let cassette = Cassette::new(&cassette_path, VcrMode::Playback).unwrap();
+    let client = Client::new("secret-key".to_string(), api_url)
+        .unwrap()
+        .with_cassette(cassette);
+
+    assert!(client.get_models().await.unwrap().get_models().is_empty());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    // the cassette contains only a single interaction
+    client.invalidate_model_cache();
+    assert!(client.get_models().await.is_err());
+
+    std::fs::remove_file(&cassette_path).unwrap();
+}
@@ -0,0 +1,21 @@
+#![cfg(target_arch = "wasm32")]
+
+use ai::{ChatCompletionParameter, CircuitBreakerConfig, CircuitState, Client};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn test_client_builder() {
+    let api_url = "https://openrouter.ai/api/v1/".parse().unwrap();
+    let client = Client::new("test-key".to_string(), api_url)
+        .unwrap()
+        .with_user_agent("rs-ai-wasm-test")
+        .unwrap()
+        .with_circuit_breaker(CircuitBreakerConfig::default());
+    assert!(matches!(client.circuit_state(), CircuitState::Closed));
+
+    let parameter = ChatCompletionParameter::new("openai/gpt-4.1".to_string(), vec![]);
+    assert!(parameter.clone().with_temperature(0.5).is_ok());
+    assert!(parameter.with_temperature(3.0).is_err());
+}